@@ -0,0 +1,209 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    DynSorter, RegisteredSorter,
+    dataset::{Distribution, Rng},
+};
+
+/// Opt-in global allocator counting the allocations of the benchmarked sorts.
+pub mod alloc;
+pub use alloc::{AllocStats, CountingAllocator};
+/// Named baselines saved to disk and statistical comparison of later runs against them.
+pub mod baseline;
+/// Empirical complexity: fits growth models to costs measured over growing input sizes.
+pub mod complexity;
+/// Exports of benchmark results to CSV, JSON, Markdown and HTML with SVG charts.
+pub mod report;
+pub use baseline::Baseline;
+/// Parameter search for configurable sorters, ranked by comparisons or time.
+pub mod tuner;
+
+/// [`Benchmarker`]
+///
+/// Times sorters on every [`Distribution`] at every size, a few samples each, and optionally
+/// records their heap usage with a [`CountingAllocator`].
+///
+/// ```
+/// use maniac::{bench::Benchmarker, dataset::Distribution, registry};
+///
+/// let benchmarker = Benchmarker {
+///     sizes: vec![10, 100],
+///     distributions: vec![Distribution::Random],
+///     samples: 3,
+///     ..Benchmarker::default()
+/// };
+/// let measurements = benchmarker.run(&registry::all());
+///
+/// let comb = measurements
+///     .iter()
+///     .find(|m| m.name == "comb" && m.len == 100)
+///     .unwrap();
+/// assert_eq!(comb.times.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Benchmarker {
+    /// Lengths of the generated inputs.
+    pub sizes: Vec<usize>,
+    /// Shapes of the generated inputs.
+    pub distributions: Vec<Distribution>,
+    /// Number of timed sorts of each input.
+    pub samples: usize,
+    /// Seed of the generated inputs, so that every sorter gets the same ones.
+    pub seed: u64,
+    /// Allocator to read the heap usage from, see [`Benchmarker::with_allocator`].
+    pub allocator: Option<&'static CountingAllocator>,
+}
+
+impl Default for Benchmarker {
+    fn default() -> Self {
+        Self {
+            sizes: vec![100, 1_000, 10_000],
+            distributions: Distribution::ALL.to_vec(),
+            samples: 5,
+            seed: 0x0062_656E_6368,
+            allocator: None,
+        }
+    }
+}
+
+/// Timings of one sorter on one input, see [`Benchmarker::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    /// Name of the algorithm, e.g. `"insertion"`.
+    pub name: String,
+    /// Name of the configuration of the algorithm, e.g. `"swap"`.
+    pub variant: String,
+    /// Shape of the input.
+    pub distribution: Distribution,
+    /// Length of the input.
+    pub len: usize,
+    /// Duration of every sample.
+    pub times: Vec<Duration>,
+    /// Heap usage of an extra, untimed sort of the input, if the benchmarker has an allocator.
+    pub allocations: Option<AllocStats>,
+}
+
+impl Measurement {
+    /// Median duration of the samples, or zero if there are none.
+    #[must_use]
+    pub fn median(&self) -> Duration {
+        let mut times = self.times.clone();
+        times.sort_unstable();
+        times.get(times.len() / 2).copied().unwrap_or_default()
+    }
+
+    /// Fastest sample, or zero if there are none.
+    #[must_use]
+    pub fn min(&self) -> Duration {
+        self.times.iter().min().copied().unwrap_or_default()
+    }
+}
+
+impl Benchmarker {
+    /// Records the heap usage of every sort with `allocator`, which must be the global allocator
+    /// of the program for the numbers to mean anything.
+    #[must_use]
+    pub fn with_allocator(mut self, allocator: &'static CountingAllocator) -> Self {
+        self.allocator = Some(allocator);
+        self
+    }
+
+    /// Benchmarks every sorter of `sorters`, typically [`registry::all`](crate::registry::all).
+    #[must_use]
+    pub fn run(&self, sorters: &[RegisteredSorter<i64>]) -> Vec<Measurement> {
+        sorters
+            .iter()
+            .flat_map(|entry| self.measure(entry.name, entry.variant, &*entry.sorter))
+            .collect()
+    }
+
+    /// Benchmarks a single sorter, reported under `name` and `variant`.
+    pub fn measure(
+        &self,
+        name: &str,
+        variant: &str,
+        sorter: &dyn DynSorter<i64>,
+    ) -> Vec<Measurement> {
+        let mut rng = Rng::new(self.seed);
+        let mut measurements = Vec::new();
+        for &distribution in &self.distributions {
+            for &len in &self.sizes {
+                let input = distribution.generate(len, &mut rng);
+                // The counting allocator slows the sort down, so its run is not timed.
+                let allocations = self.allocator.map(|allocator| {
+                    let mut data = input.clone();
+                    let ((), stats) = allocator.measure(|| sorter.sort_dyn(&mut data));
+                    stats
+                });
+                let mut times = Vec::with_capacity(self.samples);
+                for _ in 0..self.samples {
+                    let mut data = input.clone();
+                    let start = Instant::now();
+                    sorter.sort_dyn(&mut data);
+                    times.push(start.elapsed());
+                    debug_assert!(data.is_sorted(), "{name}/{variant} did not sort");
+                }
+                measurements.push(Measurement {
+                    name: name.to_string(),
+                    variant: variant.to_string(),
+                    distribution,
+                    len,
+                    times,
+                    allocations,
+                });
+            }
+        }
+        measurements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CombSorter, registry};
+
+    #[test]
+    fn measures_every_combination() {
+        let benchmarker = Benchmarker {
+            sizes: vec![0, 10, 50],
+            distributions: vec![Distribution::Random, Distribution::Sorted],
+            samples: 2,
+            ..Benchmarker::default()
+        };
+        let sorters = registry::all();
+        let measurements = benchmarker.run(&sorters);
+        assert_eq!(measurements.len(), sorters.len() * 2 * 3);
+        for measurement in &measurements {
+            assert_eq!(measurement.times.len(), 2);
+            assert_eq!(measurement.allocations, None);
+        }
+    }
+
+    #[test]
+    fn median_and_min() {
+        let measurement = Measurement {
+            name: "comb".to_string(),
+            variant: "default".to_string(),
+            distribution: Distribution::Random,
+            len: 10,
+            times: [5, 1, 3].map(Duration::from_millis).to_vec(),
+            allocations: None,
+        };
+        assert_eq!(measurement.median(), Duration::from_millis(3));
+        assert_eq!(measurement.min(), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn measures_unregistered_sorters() {
+        let benchmarker = Benchmarker {
+            sizes: vec![20],
+            distributions: vec![Distribution::Reversed],
+            samples: 1,
+            ..Benchmarker::default()
+        };
+        let sorter = CombSorter::default().with_shrink_ratio(3, 2).unwrap();
+        let measurements = benchmarker.measure("comb", "shrink_1.5", &sorter);
+        assert_eq!(measurements.len(), 1);
+        assert_eq!(measurements[0].variant, "shrink_1.5");
+    }
+}
@@ -0,0 +1,143 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// [`CountingAllocator`]
+///
+/// Wrapper around the [`System`] allocator counting every allocation, so that benchmarks can
+/// report how much memory a sort uses. It is opt-in: it only counts once installed as the global
+/// allocator of a binary, and the [`Benchmarker`](super::Benchmarker) only reports allocations
+/// when [given the allocator](super::Benchmarker::with_allocator).
+///
+/// ```
+/// use maniac::bench::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+///
+/// fn main() {
+///     let (values, stats) = ALLOCATOR.measure(|| vec![0_u64; 16]);
+///     assert_eq!(values.len(), 16);
+///     assert!(stats.allocations >= 1);
+///     assert!(stats.peak_bytes >= 128);
+/// }
+/// ```
+///
+/// The counters are global to the process: allocations made by other threads while measuring
+/// are counted too, including those of the threads spawned by parallel sorters.
+#[derive(Debug)]
+pub struct CountingAllocator {
+    allocations: AtomicUsize,
+    allocated_bytes: AtomicUsize,
+    current_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+}
+
+/// Heap usage of a measured region, see [`CountingAllocator::measure`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AllocStats {
+    /// Number of allocations and reallocations.
+    pub allocations: usize,
+    /// Total number of bytes requested by those allocations.
+    pub allocated_bytes: usize,
+    /// Largest number of bytes live at once, on top of those live before the region.
+    pub peak_bytes: usize,
+}
+
+impl fmt::Display for AllocStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} allocations, {} bytes allocated, {} bytes peak",
+            self.allocations, self.allocated_bytes, self.peak_bytes
+        )
+    }
+}
+
+impl CountingAllocator {
+    /// Creates an allocator with every counter at zero.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            allocations: AtomicUsize::new(0),
+            allocated_bytes: AtomicUsize::new(0),
+            current_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Runs `f` and returns its result along with the allocations made meanwhile.
+    pub fn measure<R>(&self, f: impl FnOnce() -> R) -> (R, AllocStats) {
+        let allocations = self.allocations.load(Ordering::SeqCst);
+        let allocated_bytes = self.allocated_bytes.load(Ordering::SeqCst);
+        let baseline = self.current_bytes.load(Ordering::SeqCst);
+        self.peak_bytes.store(baseline, Ordering::SeqCst);
+
+        let result = f();
+
+        let stats = AllocStats {
+            allocations: self.allocations.load(Ordering::SeqCst) - allocations,
+            allocated_bytes: self.allocated_bytes.load(Ordering::SeqCst) - allocated_bytes,
+            peak_bytes: self
+                .peak_bytes
+                .load(Ordering::SeqCst)
+                .saturating_sub(baseline),
+        };
+        (result, stats)
+    }
+
+    fn record_allocation(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::SeqCst);
+        self.allocated_bytes.fetch_add(size, Ordering::SeqCst);
+        let current = self.current_bytes.fetch_add(size, Ordering::SeqCst) + size;
+        self.peak_bytes.fetch_max(current, Ordering::SeqCst);
+    }
+}
+
+impl Default for CountingAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: every call is forwarded unchanged to `System`, the counters have no effect on the
+// memory handed out.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded with the caller's guarantees.
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            self.record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded with the caller's guarantees.
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            self.record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: forwarded with the caller's guarantees.
+        unsafe { System.dealloc(ptr, layout) };
+        self.current_bytes
+            .fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: forwarded with the caller's guarantees.
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            self.current_bytes
+                .fetch_sub(layout.size(), Ordering::SeqCst);
+            self.record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
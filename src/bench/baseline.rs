@@ -0,0 +1,513 @@
+use std::{
+    fmt, fs,
+    io::{self, BufRead, BufReader, Write},
+    path::{Component, Path, PathBuf, is_separator},
+    time::Duration,
+};
+
+use crate::dataset::Distribution;
+
+use super::{AllocStats, Measurement};
+
+/// First line of every baseline file, followed by the name of the baseline.
+const HEADER: &str = "# maniac baseline";
+
+/// [`Baseline`]
+///
+/// A named benchmark run saved to disk, so that later runs can be [compared](Baseline::compare)
+/// against it, e.g. before and after tuning a constant.
+///
+/// The file is plain text: a header line with the name, then one tab-separated line per
+/// measurement with every sample in nanoseconds, so that the comparison can use the whole
+/// distribution of the samples and not only their median.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Baseline {
+    /// Name of the baseline, also the stem of its file name.
+    pub name: String,
+    /// The saved measurements.
+    pub measurements: Vec<Measurement>,
+}
+
+/// Outcome of the comparison of one measurement against its baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    /// Significantly slower than the baseline.
+    Regression,
+    /// Significantly faster than the baseline.
+    Improvement,
+    /// No significant difference.
+    Unchanged,
+}
+
+/// A measurement compared to the same sorter, distribution and length in a [`Baseline`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// Name of the algorithm.
+    pub name: String,
+    /// Name of the configuration of the algorithm.
+    pub variant: String,
+    /// Shape of the input.
+    pub distribution: Distribution,
+    /// Length of the input.
+    pub len: usize,
+    /// Median duration in the baseline.
+    pub baseline: Duration,
+    /// Median duration in the current run.
+    pub current: Duration,
+    /// Relative change of the median, e.g. `0.1` for 10% slower.
+    pub change: f64,
+    /// Two-sided p-value of the Mann–Whitney U test between the samples of both runs.
+    pub p_value: f64,
+    /// Whether the difference is significant, and in which direction.
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} {} n={}: {:?} -> {:?} ({:+.1}%, p = {:.3}) {:?}",
+            self.name,
+            self.variant,
+            self.distribution.name(),
+            self.len,
+            self.baseline,
+            self.current,
+            self.change * 100.0,
+            self.p_value,
+            self.verdict
+        )
+    }
+}
+
+/// Builds the error returned for a name or field that cannot be saved in a baseline file.
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Checks that `value`, the `what` of a baseline, contains none of the `forbidden` characters,
+/// which would split it across fields or lines of the file.
+fn check_field(what: &str, value: &str, forbidden: &[char]) -> io::Result<()> {
+    if value.contains(forbidden) {
+        return Err(invalid_input(format!(
+            "{what} {value:?} contains a tab or a line break"
+        )));
+    }
+    Ok(())
+}
+
+/// Builds the error returned for a malformed baseline file.
+fn invalid(line: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("invalid baseline, line {line}: {message}"),
+    )
+}
+
+impl Baseline {
+    /// Creates a baseline named `name` from `measurements`.
+    pub fn new(name: impl Into<String>, measurements: Vec<Measurement>) -> Self {
+        Self {
+            name: name.into(),
+            measurements,
+        }
+    }
+
+    /// Path of the file of the baseline `name` in `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`io::ErrorKind::InvalidInput`] error if `name` is not a plain file name, e.g.
+    /// `../main` or an absolute path, which would put the file outside of `dir`.
+    pub fn path(dir: &Path, name: &str) -> io::Result<PathBuf> {
+        // A single normal component rules out `..`, roots and drive prefixes; the characters are
+        // checked too, as components drop trailing separators and `.`.
+        let mut components = Path::new(name).components();
+        let plain = matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        ) && !name.contains(|c| is_separator(c) || matches!(c, '\0' | '\n' | '\r'));
+        if !plain {
+            return Err(invalid_input(format!(
+                "baseline name {name:?} is not a plain file name"
+            )));
+        }
+        Ok(dir.join(format!("{name}.baseline")))
+    }
+
+    /// Writes the baseline in its text format.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `writer`, or an [`io::ErrorKind::InvalidInput`] error, before
+    /// writing anything, if the name of the baseline contains a line break, or the name or
+    /// variant of a measurement a tab or a line break.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        check_field("baseline name", &self.name, &['\n', '\r'])?;
+        for measurement in &self.measurements {
+            check_field("name", &measurement.name, &['\t', '\n', '\r'])?;
+            check_field("variant", &measurement.variant, &['\t', '\n', '\r'])?;
+        }
+
+        writeln!(writer, "{HEADER} {}", self.name)?;
+        for measurement in &self.measurements {
+            let times: Vec<String> = measurement
+                .times
+                .iter()
+                .map(|time| time.as_nanos().to_string())
+                .collect();
+            let allocations = measurement.allocations.map_or_else(
+                || "-".to_string(),
+                |stats| {
+                    format!(
+                        "{},{},{}",
+                        stats.allocations, stats.allocated_bytes, stats.peak_bytes
+                    )
+                },
+            );
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}\t{allocations}",
+                measurement.name,
+                measurement.variant,
+                measurement.distribution.name(),
+                measurement.len,
+                times.join(","),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reads a baseline written by [`Baseline::write_to`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `reader`, or an [`io::ErrorKind::InvalidData`] error if the contents
+    /// are not a baseline.
+    pub fn read_from(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let name = header
+            .strip_prefix(HEADER)
+            .and_then(|name| name.strip_prefix(' '))
+            .ok_or_else(|| invalid(1, "missing header"))?;
+
+        let mut measurements = Vec::new();
+        for (index, line) in lines.enumerate() {
+            let line = line?;
+            let line_number = index + 2;
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [name, variant, distribution, len, times, allocations] = fields[..] else {
+                return Err(invalid(line_number, "expected 6 tab-separated fields"));
+            };
+            let number = |field: &str| {
+                field
+                    .parse::<usize>()
+                    .map_err(|_| invalid(line_number, "expected a number"))
+            };
+            let times = times
+                .split(',')
+                .filter(|time| !time.is_empty())
+                .map(|time| {
+                    time.parse()
+                        .map(Duration::from_nanos)
+                        .map_err(|_| invalid(line_number, "expected durations in nanoseconds"))
+                })
+                .collect::<io::Result<_>>()?;
+            let allocations = match allocations.split(',').collect::<Vec<_>>()[..] {
+                ["-"] => None,
+                [allocations, allocated_bytes, peak_bytes] => Some(AllocStats {
+                    allocations: number(allocations)?,
+                    allocated_bytes: number(allocated_bytes)?,
+                    peak_bytes: number(peak_bytes)?,
+                }),
+                _ => return Err(invalid(line_number, "expected 3 allocation counters or -")),
+            };
+            measurements.push(Measurement {
+                name: name.to_string(),
+                variant: variant.to_string(),
+                distribution: Distribution::from_name(distribution)
+                    .ok_or_else(|| invalid(line_number, "unknown distribution"))?,
+                len: number(len)?,
+                times,
+                allocations,
+            });
+        }
+        Ok(Self::new(name, measurements))
+    }
+
+    /// Saves the baseline to `dir`, as [`Baseline::path`], and returns the path of the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O errors of creating and writing the file, or an
+    /// [`io::ErrorKind::InvalidInput`] error if the baseline cannot be saved under its name, see
+    /// [`Baseline::path`] and [`Baseline::write_to`].
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        let path = Self::path(dir, &self.name)?;
+        let mut file = io::BufWriter::new(fs::File::create(&path)?);
+        self.write_to(&mut file)?;
+        file.flush()?;
+        Ok(path)
+    }
+
+    /// Loads the baseline `name` saved to `dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O errors of reading the file, an [`io::ErrorKind::InvalidInput`] error if
+    /// `name` is not a plain file name, or an [`io::ErrorKind::InvalidData`] error if the file is
+    /// not a baseline.
+    pub fn load(dir: &Path, name: &str) -> io::Result<Self> {
+        Self::read_from(BufReader::new(fs::File::open(Self::path(dir, name)?)?))
+    }
+
+    /// Compares every measurement of `current` that has a counterpart in the baseline, in the
+    /// order of `current`. A difference is significant when the Mann–Whitney U test rejects equal
+    /// distributions at the `significance` level, e.g. `0.05`.
+    #[must_use]
+    pub fn compare(&self, current: &[Measurement], significance: f64) -> Vec<Comparison> {
+        current
+            .iter()
+            .filter_map(|measurement| {
+                let baseline = self.measurements.iter().find(|baseline| {
+                    baseline.name == measurement.name
+                        && baseline.variant == measurement.variant
+                        && baseline.distribution == measurement.distribution
+                        && baseline.len == measurement.len
+                })?;
+                let (baseline_median, current_median) = (baseline.median(), measurement.median());
+                let change = current_median.as_secs_f64() / baseline_median.as_secs_f64() - 1.0;
+                let p_value = mann_whitney(&baseline.times, &measurement.times);
+                let verdict = if p_value >= significance || change == 0.0 || change.is_nan() {
+                    Verdict::Unchanged
+                } else if change > 0.0 {
+                    Verdict::Regression
+                } else {
+                    Verdict::Improvement
+                };
+                Some(Comparison {
+                    name: measurement.name.clone(),
+                    variant: measurement.variant.clone(),
+                    distribution: measurement.distribution,
+                    len: measurement.len,
+                    baseline: baseline_median,
+                    current: current_median,
+                    change,
+                    p_value,
+                    verdict,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Two-sided p-value of the Mann–Whitney U test between two samples, with the normal
+/// approximation corrected for ties and continuity. Returns `1` if either sample is empty.
+#[allow(clippy::cast_precision_loss)]
+fn mann_whitney(before: &[Duration], after: &[Duration]) -> f64 {
+    if before.is_empty() || after.is_empty() {
+        return 1.0;
+    }
+    let mut pooled: Vec<(Duration, bool)> = before
+        .iter()
+        .map(|&time| (time, true))
+        .chain(after.iter().map(|&time| (time, false)))
+        .collect();
+    pooled.sort_unstable();
+
+    // Average ranks (1-based) over runs of ties.
+    let (n1, n2) = (before.len() as f64, after.len() as f64);
+    let total = n1 + n2;
+    let mut rank_sum = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < pooled.len() {
+        let end = start
+            + pooled[start..]
+                .iter()
+                .take_while(|(time, _)| *time == pooled[start].0)
+                .count();
+        let average_rank = (start + end + 1) as f64 / 2.0;
+        let in_before = pooled[start..end]
+            .iter()
+            .filter(|(_, in_before)| *in_before)
+            .count();
+        rank_sum += average_rank * in_before as f64;
+        let ties = (end - start) as f64;
+        tie_correction += ties * ties * ties - ties;
+        start = end;
+    }
+
+    let u_statistic = rank_sum - n1 * (n1 + 1.0) / 2.0;
+    let mean = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((total + 1.0) - tie_correction / (total * (total - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    let z_score = ((u_statistic - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    (2.0 * normal_upper_tail(z_score)).min(1.0)
+}
+
+/// `P(Z > z)` for a standard normal `Z`, from the complementary error function (Numerical
+/// Recipes' Chebyshev approximation, accurate to about 1e-7).
+fn normal_upper_tail(z: f64) -> f64 {
+    let x = z / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let polynomial = [
+        -1.265_512_23,
+        1.000_023_68,
+        0.374_091_96,
+        0.096_784_18,
+        -0.186_288_06,
+        0.278_868_07,
+        -1.135_203_98,
+        1.488_515_87,
+        -0.822_152_23,
+        0.170_872_77,
+    ]
+    .iter()
+    .rev()
+    .fold(0.0, |acc, &coefficient| acc * t + coefficient);
+    let erfc = t * (-x * x + polynomial).exp();
+    let erfc = if x >= 0.0 { erfc } else { 2.0 - erfc };
+    erfc / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(name: &str, len: usize, nanos: &[u64]) -> Measurement {
+        Measurement {
+            name: name.to_string(),
+            variant: "default".to_string(),
+            distribution: Distribution::NearlySorted,
+            len,
+            times: nanos.iter().copied().map(Duration::from_nanos).collect(),
+            allocations: None,
+        }
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let mut with_allocations = measurement("standard", 100, &[10, 12]);
+        with_allocations.allocations = Some(AllocStats {
+            allocations: 1,
+            allocated_bytes: 400,
+            peak_bytes: 400,
+        });
+        let baseline = Baseline::new(
+            "before tuning",
+            vec![measurement("comb", 10, &[5, 6, 7]), with_allocations],
+        );
+        let mut text = Vec::new();
+        baseline.write_to(&mut text).unwrap();
+        assert_eq!(Baseline::read_from(&text[..]).unwrap(), baseline);
+    }
+
+    #[test]
+    fn saves_and_loads_files() {
+        let dir = std::env::temp_dir().join(format!("maniac-baseline-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let baseline = Baseline::new("main", vec![measurement("comb", 10, &[5, 6, 7])]);
+        let path = baseline.save(&dir).unwrap();
+        assert_eq!(path, dir.join("main.baseline"));
+        assert_eq!(Baseline::load(&dir, "main").unwrap(), baseline);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_names_escaping_the_directory() {
+        let dir = Path::new("baselines");
+        assert_eq!(
+            Baseline::path(dir, "main").unwrap(),
+            dir.join("main.baseline")
+        );
+        for name in ["", ".", "..", "../x", "a/b", "/etc/x", "a\nb"] {
+            let error = Baseline::path(dir, name).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{name:?}");
+            let error = Baseline::load(dir, name).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{name:?}");
+        }
+        let error = Baseline::new("../main", Vec::new()).save(dir).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn rejects_fields_breaking_the_format() {
+        let mut tabbed = measurement("comb", 10, &[5]);
+        tabbed.variant = "a\tb".to_string();
+        for baseline in [
+            Baseline::new("two\nlines", Vec::new()),
+            Baseline::new("main", vec![measurement("co\nmb", 10, &[5])]),
+            Baseline::new("main", vec![tabbed]),
+        ] {
+            let mut text = Vec::new();
+            let error = baseline.write_to(&mut text).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{baseline:?}");
+            assert!(text.is_empty());
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        for text in [
+            "",
+            "# maniac baseline main\ncomb\tdefault",
+            "# maniac baseline main\ncomb\tdefault\tnowhere\t10\t5\t-",
+            "# maniac baseline main\ncomb\tdefault\trandom\tten\t5\t-",
+        ] {
+            let error = Baseline::read_from(text.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{text}");
+        }
+    }
+
+    #[test]
+    fn p_values() {
+        let nanos = |values: &[u64]| -> Vec<Duration> {
+            values.iter().copied().map(Duration::from_nanos).collect()
+        };
+        let same = mann_whitney(&nanos(&[1, 2, 3, 4, 5]), &nanos(&[1, 2, 3, 4, 5]));
+        assert!((same - 1.0).abs() < 1e-9, "{same}");
+        let apart = mann_whitney(&nanos(&[1, 2, 3, 4, 5]), &nanos(&[6, 7, 8, 9, 10]));
+        assert!(apart < 0.02, "{apart}");
+        let overlapping = mann_whitney(&nanos(&[1, 3, 5, 7, 9]), &nanos(&[2, 4, 6, 8, 10]));
+        assert!(overlapping > 0.5, "{overlapping}");
+        assert!((normal_upper_tail(1.96) - 0.025).abs() < 1e-4);
+        assert!((normal_upper_tail(-1.0) - 0.841_344_7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn flags_regressions_and_improvements() {
+        let baseline = Baseline::new(
+            "main",
+            vec![
+                measurement("comb", 100, &[100, 101, 102, 103, 104, 105]),
+                measurement("gnome", 100, &[100, 101, 102, 103, 104, 105]),
+                measurement("bubble", 100, &[100, 101, 102, 103, 104, 105]),
+            ],
+        );
+        let current = [
+            measurement("comb", 100, &[150, 151, 152, 153, 154, 155]),
+            measurement("gnome", 100, &[50, 51, 52, 53, 54, 55]),
+            measurement("bubble", 100, &[99, 101, 102, 103, 104, 106]),
+            measurement("odd_even", 100, &[1]),
+        ];
+        let comparisons = baseline.compare(&current, 0.05);
+        let verdicts: Vec<(&str, Verdict)> = comparisons
+            .iter()
+            .map(|comparison| (comparison.name.as_str(), comparison.verdict))
+            .collect();
+        assert_eq!(
+            verdicts,
+            [
+                ("comb", Verdict::Regression),
+                ("gnome", Verdict::Improvement),
+                ("bubble", Verdict::Unchanged),
+            ]
+        );
+        assert!((comparisons[0].change - 50.0 / 103.0).abs() < 1e-9);
+        assert!(comparisons[0].to_string().contains("+48.5%"));
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
+
+use crate::{Complexity, Sorter, dataset::Distribution};
+
+use super::Measurement;
+
+/// Candidate growth model of a cost as a function of the input length `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Model {
+    /// *n*
+    Linear,
+    /// *n log n*
+    Linearithmic,
+    /// *n^1.5*
+    PowerThreeHalves,
+    /// *n²*
+    Quadratic,
+}
+
+impl Model {
+    /// Every model, from the slowest growing to the fastest.
+    pub const ALL: [Self; 4] = [
+        Self::Linear,
+        Self::Linearithmic,
+        Self::PowerThreeHalves,
+        Self::Quadratic,
+    ];
+
+    /// Value of the model at `n`, up to a constant factor.
+    #[must_use]
+    pub fn eval(self, n: f64) -> f64 {
+        match self {
+            Self::Linear => n,
+            Self::Linearithmic => n * n.log2().max(1.0),
+            Self::PowerThreeHalves => n * n.sqrt(),
+            Self::Quadratic => n * n,
+        }
+    }
+
+    /// The [`Complexity`] this model stands for, if there is one.
+    #[must_use]
+    pub fn complexity(self) -> Option<Complexity> {
+        match self {
+            Self::Linear => Some(Complexity::Linear),
+            Self::Linearithmic => Some(Complexity::Linearithmic),
+            Self::PowerThreeHalves => None,
+            Self::Quadratic => Some(Complexity::Quadratic),
+        }
+    }
+
+    /// Whether this model grows no faster than `complexity`.
+    #[must_use]
+    pub fn is_within(self, complexity: Complexity) -> bool {
+        let bound = match complexity {
+            Complexity::Constant | Complexity::Logarithmic | Complexity::Linear => Self::Linear,
+            Complexity::Linearithmic => Self::Linearithmic,
+            Complexity::Quadratic => Self::Quadratic,
+        };
+        self <= bound
+    }
+}
+
+impl fmt::Display for Model {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Linear => "O(n)",
+            Self::Linearithmic => "O(n log n)",
+            Self::PowerThreeHalves => "O(n^1.5)",
+            Self::Quadratic => "O(n²)",
+        })
+    }
+}
+
+/// Best [`Model`] for a series of costs, see [`fit`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+    /// The model that explains the costs best.
+    pub model: Model,
+    /// Probability that [`model`](Fit::model) is the best of the candidates (its Akaike weight),
+    /// between `0` and `1`.
+    pub confidence: f64,
+    /// Slope of the costs on a log-log scale, e.g. about `2` for a quadratic cost.
+    pub exponent: f64,
+    /// Akaike weight of every model, most likely first.
+    pub weights: Vec<(Model, f64)>,
+}
+
+impl fmt::Display for Fit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({:.0}% confidence, exponent {:.2})",
+            self.model,
+            self.confidence * 100.0,
+            self.exponent
+        )
+    }
+}
+
+/// Fits every [`Model`] to `points`, pairs of input length and cost (a duration in any unit, a
+/// number of comparisons...), and returns the best one. Returns `None` with fewer than three
+/// distinct lengths, which cannot tell the models apart.
+///
+/// Each model `c · f(n)` is fitted on a log scale, so that every length weighs the same whatever
+/// its cost, and the models are ranked by their residual sum of squares. As they all have a single
+/// parameter, their Akaike weights reduce to `RSS^(-k/2)`, normalized, for `k` points.
+///
+/// ```
+/// use maniac::bench::complexity::{Model, fit};
+///
+/// let points: Vec<(usize, f64)> = [100, 200, 400, 800]
+///     .iter()
+///     .map(|&n| (n, 3.0 * (n * n) as f64))
+///     .collect();
+/// let fit = fit(&points).unwrap();
+///
+/// assert_eq!(fit.model, Model::Quadratic);
+/// assert!(fit.confidence > 0.99);
+/// assert!((fit.exponent - 2.0).abs() < 1e-9);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn fit(points: &[(usize, f64)]) -> Option<Fit> {
+    let mut lengths: Vec<usize> = points.iter().map(|&(n, _)| n).collect();
+    lengths.sort_unstable();
+    lengths.dedup();
+    if lengths.len() < 3 || lengths[0] == 0 {
+        return None;
+    }
+
+    // Costs of zero (e.g. a sort too fast for the clock) would have no logarithm.
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .map(|&(n, cost)| (n as f64, cost.max(f64::MIN_POSITIVE).ln()))
+        .collect();
+    let count = logs.len() as f64;
+
+    let mut rss: Vec<(Model, f64)> = Model::ALL
+        .iter()
+        .map(|&model| {
+            let residuals: Vec<f64> = logs
+                .iter()
+                .map(|&(n, log_cost)| log_cost - model.eval(n).ln())
+                .collect();
+            let log_factor = residuals.iter().sum::<f64>() / count;
+            let rss = residuals
+                .iter()
+                .map(|residual| (residual - log_factor).powi(2))
+                .sum::<f64>();
+            (model, rss.max(1e-12))
+        })
+        .collect();
+    rss.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    let best_rss = rss[0].1;
+    let relative: Vec<(Model, f64)> = rss
+        .iter()
+        .map(|&(model, rss)| (model, (best_rss / rss).powf(count / 2.0)))
+        .collect();
+    let total: f64 = relative.iter().map(|&(_, weight)| weight).sum();
+    let weights: Vec<(Model, f64)> = relative
+        .into_iter()
+        .map(|(model, weight)| (model, weight / total))
+        .collect();
+
+    let mean_log_n = logs.iter().map(|&(n, _)| n.ln()).sum::<f64>() / count;
+    let mean_log_cost = logs.iter().map(|&(_, cost)| cost).sum::<f64>() / count;
+    let (covariance, variance) =
+        logs.iter()
+            .fold((0.0, 0.0), |(covariance, variance), &(n, cost)| {
+                let dx = n.ln() - mean_log_n;
+                (covariance + dx * (cost - mean_log_cost), variance + dx * dx)
+            });
+
+    Some(Fit {
+        model: weights[0].0,
+        confidence: weights[0].1,
+        exponent: covariance / variance,
+        weights,
+    })
+}
+
+/// Growth class of one sorter on one distribution, see [`estimate`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    /// Name of the algorithm.
+    pub name: String,
+    /// Name of the configuration of the algorithm.
+    pub variant: String,
+    /// Shape of the inputs.
+    pub distribution: Distribution,
+    /// Best model for the median durations.
+    pub fit: Fit,
+}
+
+/// Fits the median durations of `measurements` for every sorter and distribution measured at
+/// three sizes or more, in the order they first appear.
+#[must_use]
+pub fn estimate(measurements: &[Measurement]) -> Vec<Estimate> {
+    let mut groups: Vec<(&Measurement, Vec<(usize, f64)>)> = Vec::new();
+    for measurement in measurements {
+        let point = (measurement.len, measurement.median().as_secs_f64());
+        let group = groups.iter_mut().find(|(first, _)| {
+            first.name == measurement.name
+                && first.variant == measurement.variant
+                && first.distribution == measurement.distribution
+        });
+        match group {
+            Some((_, points)) => points.push(point),
+            None => groups.push((measurement, vec![point])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|(first, points)| {
+            Some(Estimate {
+                name: first.name.clone(),
+                variant: first.variant.clone(),
+                distribution: first.distribution,
+                fit: fit(&points)?,
+            })
+        })
+        .collect()
+}
+
+/// Element counting the comparisons made on it, a cost that does not depend on the machine.
+#[derive(Debug, Clone, Copy)]
+pub struct Counted<'a> {
+    value: i64,
+    comparisons: &'a AtomicUsize,
+}
+
+impl<'a> Counted<'a> {
+    /// Creates an element holding `value` that increments `comparisons` when compared.
+    pub fn new(value: i64, comparisons: &'a AtomicUsize) -> Self {
+        Self { value, comparisons }
+    }
+}
+
+impl Ord for Counted<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.comparisons.fetch_add(1, AtomicOrdering::Relaxed);
+        self.value.cmp(&other.value)
+    }
+}
+
+impl PartialOrd for Counted<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Counted<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Counted<'_> {}
+
+/// Number of comparisons `sorter` makes to sort `input`.
+pub fn count_comparisons<'a, S: Sorter<Counted<'a>>>(
+    sorter: S,
+    input: &[i64],
+    comparisons: &'a AtomicUsize,
+) -> usize {
+    let mut elements: Vec<Counted<'a>> = input
+        .iter()
+        .map(|&value| Counted::new(value, comparisons))
+        .collect();
+    let before = comparisons.load(AtomicOrdering::Relaxed);
+    sorter.sort(&mut elements);
+    comparisons.load(AtomicOrdering::Relaxed) - before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bench::Benchmarker, dataset::Rng, registry};
+
+    #[allow(clippy::cast_precision_loss)]
+    fn points(model: Model, noise: f64) -> Vec<(usize, f64)> {
+        let mut rng = Rng::new(11);
+        [128, 256, 512, 1024, 2048, 4096]
+            .iter()
+            .map(|&n| {
+                let jitter = 1.0 + noise * (rng.below(1000) as f64 / 500.0 - 1.0);
+                (n, 7.0 * model.eval(n as f64) * jitter)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recovers_every_model_through_noise() {
+        for model in Model::ALL {
+            let fit = fit(&points(model, 0.05)).unwrap();
+            assert_eq!(fit.model, model);
+            assert!(fit.confidence > 0.9, "{fit:?}");
+            let total: f64 = fit.weights.iter().map(|&(_, weight)| weight).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn needs_three_lengths() {
+        assert_eq!(fit(&[(10, 1.0), (20, 4.0), (20, 4.0)]), None);
+        assert_eq!(fit(&[(0, 1.0), (20, 4.0), (40, 16.0)]), None);
+    }
+
+    #[test]
+    fn estimates_per_sorter_and_distribution() {
+        let benchmarker = Benchmarker {
+            sizes: vec![16, 32, 64],
+            distributions: vec![Distribution::Random, Distribution::Sorted],
+            samples: 1,
+            ..Benchmarker::default()
+        };
+        let sorter = registry::find::<i64>("gnome", "default").unwrap();
+        let measurements = benchmarker.measure(sorter.name, sorter.variant, &*sorter.sorter);
+        let estimates = estimate(&measurements);
+        assert_eq!(estimates.len(), 2);
+        assert_eq!(estimates[1].distribution, Distribution::Sorted);
+        assert!(
+            estimates[0]
+                .fit
+                .to_string()
+                .contains("% confidence, exponent")
+        );
+    }
+
+    /// Checks the documented complexities of every sorter against its comparison counts, which
+    /// unlike timings are exact. Comparisons are a lower bound of the work (rotations move
+    /// elements without comparing them), so the fit must stay within the documented class.
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn comparison_counts_match_documented_complexities() {
+        let comparisons = AtomicUsize::new(0);
+        let mut rng = Rng::new(3);
+        let sizes = [128, 256, 512, 1024];
+        let inputs: Vec<(Distribution, Vec<Vec<i64>>)> =
+            [Distribution::Random, Distribution::Sorted]
+                .into_iter()
+                .map(|distribution| {
+                    let inputs = sizes.map(|len| distribution.generate(len, &mut rng));
+                    (distribution, inputs.to_vec())
+                })
+                .collect();
+
+        for entry in registry::all::<Counted<'_>>() {
+            for (distribution, inputs) in &inputs {
+                let points: Vec<(usize, f64)> = inputs
+                    .iter()
+                    .map(|input| {
+                        let count = count_comparisons(&*entry.sorter, input, &comparisons);
+                        (input.len(), count as f64)
+                    })
+                    .collect();
+                let fit = fit(&points).unwrap();
+                let documented = match distribution {
+                    Distribution::Sorted => entry.metadata.best,
+                    _ => entry.metadata.average,
+                };
+                assert!(
+                    fit.model.is_within(documented),
+                    "{}/{} on {distribution:?}: fitted {} but documented {documented}",
+                    entry.name,
+                    entry.variant,
+                    fit.model
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn exchange_sorts_are_quadratic_and_comb_is_not() {
+        let comparisons = AtomicUsize::new(0);
+        let mut rng = Rng::new(4);
+        let inputs: Vec<Vec<i64>> = [128, 256, 512, 1024]
+            .map(|len| Distribution::Random.generate(len, &mut rng))
+            .to_vec();
+        for (name, expected) in [
+            ("gnome", Model::Quadratic),
+            ("cocktail_shaker", Model::Quadratic),
+            ("comb", Model::Linearithmic),
+        ] {
+            let entry = registry::all::<Counted<'_>>()
+                .into_iter()
+                .find(|entry| entry.name == name)
+                .unwrap();
+            let points: Vec<(usize, f64)> = inputs
+                .iter()
+                .map(|input| {
+                    let count = count_comparisons(&*entry.sorter, input, &comparisons);
+                    (input.len(), count as f64)
+                })
+                .collect();
+            assert_eq!(fit(&points).unwrap().model, expected, "{name}");
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::Write;
+
+use crate::dataset::Distribution;
+
+use super::Measurement;
+
+/// Columns shared by the [`csv`] and [`json`] reports.
+const COLUMNS: [&str; 10] = [
+    "name",
+    "variant",
+    "distribution",
+    "len",
+    "samples",
+    "median_ns",
+    "min_ns",
+    "allocations",
+    "allocated_bytes",
+    "peak_bytes",
+];
+
+/// Values of the [`COLUMNS`] of `measurement`, numbers unquoted and missing values empty.
+fn row(measurement: &Measurement) -> [String; 10] {
+    let allocations = measurement.allocations;
+    let optional = |value: Option<usize>| value.map(|value| value.to_string()).unwrap_or_default();
+    [
+        measurement.name.clone(),
+        measurement.variant.clone(),
+        measurement.distribution.name().to_string(),
+        measurement.len.to_string(),
+        measurement.times.len().to_string(),
+        measurement.median().as_nanos().to_string(),
+        measurement.min().as_nanos().to_string(),
+        optional(allocations.map(|stats| stats.allocations)),
+        optional(allocations.map(|stats| stats.allocated_bytes)),
+        optional(allocations.map(|stats| stats.peak_bytes)),
+    ]
+}
+
+/// Renders `measurements` as CSV with a header line, durations in nanoseconds. Allocation columns
+/// are empty when allocations were not tracked.
+#[must_use]
+pub fn csv(measurements: &[Measurement]) -> String {
+    let quote = |field: &str| {
+        if field.contains([',', '"', '\n']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    };
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for measurement in measurements {
+        let fields: Vec<String> = row(measurement).iter().map(|field| quote(field)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Escapes `s` as the contents of a JSON string.
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(escaped, "\\u{:04x}", u32::from(c));
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Renders `measurements` as a JSON array of objects, one per measurement, with the columns of
+/// the [`csv`] report (`null` when allocations were not tracked) and every sample in `times_ns`.
+#[must_use]
+pub fn json(measurements: &[Measurement]) -> String {
+    let mut out = String::from("[");
+    for (index, measurement) in measurements.iter().enumerate() {
+        out.push_str(if index == 0 { "\n  {" } else { ",\n  {" });
+        for (column, (name, value)) in COLUMNS.iter().zip(row(measurement)).enumerate() {
+            let value = if column < 3 {
+                format!("\"{}\"", json_escape(&value))
+            } else if value.is_empty() {
+                "null".to_string()
+            } else {
+                value
+            };
+            let _ = write!(out, "\"{name}\": {value}, ");
+        }
+        let times: Vec<String> = measurement
+            .times
+            .iter()
+            .map(|time| time.as_nanos().to_string())
+            .collect();
+        let _ = write!(out, "\"times_ns\": [{}]}}", times.join(", "));
+    }
+    out.push_str(if measurements.is_empty() {
+        "]\n"
+    } else {
+        "\n]\n"
+    });
+    out
+}
+
+/// Renders `measurements` as a Markdown table, e.g. for a pull request comment.
+#[must_use]
+pub fn markdown(measurements: &[Measurement]) -> String {
+    let mut out = String::from(
+        "| Sorter | Distribution | n | Median | Min | Allocations | Peak bytes |\n\
+         | ------ | ------------ | -: | -----: | --: | ----------: | ---------: |\n",
+    );
+    for measurement in measurements {
+        let (allocations, peak) = measurement.allocations.map_or_else(
+            || ("-".to_string(), "-".to_string()),
+            |stats| (stats.allocations.to_string(), stats.peak_bytes.to_string()),
+        );
+        let _ = writeln!(
+            out,
+            "| {}/{} | {} | {} | {:?} | {:?} | {allocations} | {peak} |",
+            measurement.name.replace('|', "\\|"),
+            measurement.variant.replace('|', "\\|"),
+            measurement.distribution.name(),
+            measurement.len,
+            measurement.median(),
+            measurement.min(),
+        );
+    }
+    out
+}
+
+/// Escapes `s` for HTML text and attribute values.
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Line colors of the charts, reused when there are more series.
+const PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// One line of a chart: a label and its `(n, nanoseconds)` points, sorted by `n`.
+type Series = (String, Vec<(f64, f64)>);
+
+/// Draws `series` as an SVG line chart of the median time against `n`, on log-log axes if `log`.
+#[allow(clippy::cast_precision_loss)]
+fn svg_chart(title: &str, series: &[Series], log: bool) -> String {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 360.0;
+    const MARGIN: f64 = 56.0;
+    const LEGEND: f64 = 180.0;
+
+    let scale = |value: f64| if log { value.max(1.0).log10() } else { value };
+    let points = series.iter().flat_map(|(_, points)| points);
+    let (mut x_min, mut x_max, mut y_min, mut y_max) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for &(x, y) in points {
+        (x_min, x_max) = (x_min.min(scale(x)), x_max.max(scale(x)));
+        (y_min, y_max) = (y_min.min(scale(y)), y_max.max(scale(y)));
+    }
+    if !log {
+        (x_min, y_min) = (0.0, 0.0);
+    }
+    if x_max <= x_min {
+        x_max = x_min + 1.0;
+    }
+    if y_max <= y_min {
+        y_max = y_min + 1.0;
+    }
+    let plot_width = WIDTH - 2.0 * MARGIN;
+    let plot_height = HEIGHT - 2.0 * MARGIN;
+    let x_pos = |x: f64| MARGIN + (scale(x) - x_min) / (x_max - x_min) * plot_width;
+    let y_pos = |y: f64| HEIGHT - MARGIN - (scale(y) - y_min) / (y_max - y_min) * plot_height;
+    let unscale = |value: f64| if log { 10_f64.powf(value) } else { value };
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{HEIGHT}\" \
+         font-family=\"sans-serif\" font-size=\"11\">\n\
+         <text x=\"{MARGIN}\" y=\"20\" font-size=\"14\">{}</text>\n\
+         <path d=\"M{MARGIN},{MARGIN}V{}H{}\" fill=\"none\" stroke=\"#333\"/>\n",
+        WIDTH + LEGEND,
+        html_escape(title),
+        HEIGHT - MARGIN,
+        WIDTH - MARGIN,
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{MARGIN}\" y=\"{}\">{:.0}</text>\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\">n = {:.0}</text>\n\
+         <text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.0} ns</text>\
+         <text x=\"{}\" y=\"{MARGIN}\" text-anchor=\"end\">{:.0} ns</text>\n",
+        HEIGHT - MARGIN + 16.0,
+        unscale(x_min),
+        WIDTH - MARGIN,
+        HEIGHT - MARGIN + 16.0,
+        unscale(x_max),
+        MARGIN - 4.0,
+        HEIGHT - MARGIN,
+        unscale(y_min),
+        MARGIN - 4.0,
+        unscale(y_max),
+    );
+    for (index, (label, points)) in series.iter().enumerate() {
+        let color = PALETTE[index % PALETTE.len()];
+        let path: Vec<String> = points
+            .iter()
+            .map(|&(x, y)| format!("{:.1},{:.1}", x_pos(x), y_pos(y)))
+            .collect();
+        let legend_y = MARGIN + 16.0 * index as f64;
+        let _ = write!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{color}\" stroke-width=\"2\"/>\n\
+             <rect x=\"{}\" y=\"{}\" width=\"10\" height=\"10\" fill=\"{color}\"/>\
+             <text x=\"{}\" y=\"{legend_y}\">{}</text>\n",
+            path.join(" "),
+            WIDTH + 4.0,
+            legend_y - 9.0,
+            WIDTH + 18.0,
+            html_escape(label),
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Renders `measurements` as a self-contained HTML page: for every distribution, a chart of the
+/// median time against `n`, the same chart on log-log axes, then the [`markdown`] table as HTML.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn html(measurements: &[Measurement]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>maniac benchmark</title>\n\
+         <style>body{font-family:sans-serif}table{border-collapse:collapse}\
+         td,th{border:1px solid #ccc;padding:2px 8px;text-align:right}</style>\n\
+         </head>\n<body>\n<h1>maniac benchmark</h1>\n",
+    );
+
+    let mut distributions: Vec<Distribution> = Vec::new();
+    for measurement in measurements {
+        if !distributions.contains(&measurement.distribution) {
+            distributions.push(measurement.distribution);
+        }
+    }
+    for distribution in distributions {
+        let mut series: Vec<Series> = Vec::new();
+        for measurement in measurements
+            .iter()
+            .filter(|measurement| measurement.distribution == distribution)
+        {
+            let label = format!("{}/{}", measurement.name, measurement.variant);
+            let point = (
+                measurement.len as f64,
+                measurement.median().as_nanos() as f64,
+            );
+            match series.iter_mut().find(|(existing, _)| *existing == label) {
+                Some((_, points)) => points.push(point),
+                None => series.push((label, vec![point])),
+            }
+        }
+        for (_, points) in &mut series {
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        let name = distribution.name();
+        let _ = write!(
+            out,
+            "<h2>{name}</h2>\n{}{}",
+            svg_chart(&format!("{name}: time vs n"), &series, false),
+            svg_chart(&format!("{name}: time vs n (log-log)"), &series, true),
+        );
+    }
+
+    out.push_str(
+        "<h2>Measurements</h2>\n<table>\n<tr><th>Sorter</th><th>Distribution</th><th>n</th>\
+         <th>Median</th><th>Min</th><th>Allocations</th><th>Peak bytes</th></tr>\n",
+    );
+    for measurement in measurements {
+        let (allocations, peak) = measurement.allocations.map_or_else(
+            || ("-".to_string(), "-".to_string()),
+            |stats| (stats.allocations.to_string(), stats.peak_bytes.to_string()),
+        );
+        let _ = writeln!(
+            out,
+            "<tr><td>{}/{}</td><td>{}</td><td>{}</td><td>{:?}</td><td>{:?}</td>\
+             <td>{allocations}</td><td>{peak}</td></tr>",
+            html_escape(&measurement.name),
+            html_escape(&measurement.variant),
+            measurement.distribution.name(),
+            measurement.len,
+            measurement.median(),
+            measurement.min(),
+        );
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::bench::AllocStats;
+
+    fn measurements() -> Vec<Measurement> {
+        let measurement = |name: &str, len: usize, nanos: u64, allocations| Measurement {
+            name: name.to_string(),
+            variant: "default".to_string(),
+            distribution: Distribution::Random,
+            len,
+            times: vec![
+                Duration::from_nanos(nanos),
+                Duration::from_nanos(nanos + 10),
+            ],
+            allocations,
+        };
+        let stats = AllocStats {
+            allocations: 1,
+            allocated_bytes: 800,
+            peak_bytes: 800,
+        };
+        vec![
+            measurement("comb", 10, 100, None),
+            measurement("comb", 100, 1_500, None),
+            measurement("standard", 10, 80, Some(stats)),
+            measurement("odd,\"even\"", 100, 900, None),
+        ]
+    }
+
+    #[test]
+    fn csv_report() {
+        let csv = csv(&measurements());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], COLUMNS.join(","));
+        assert_eq!(lines[1], "comb,default,random,10,2,110,100,,,");
+        assert_eq!(lines[3], "standard,default,random,10,2,90,80,1,800,800");
+        assert!(lines[4].starts_with("\"odd,\"\"even\"\"\",default"));
+    }
+
+    #[test]
+    fn json_report() {
+        let json = json(&measurements());
+        assert!(json.starts_with("[\n  {\"name\": \"comb\", "));
+        assert!(json.contains("\"allocations\": null"));
+        assert!(json.contains("\"peak_bytes\": 800, \"times_ns\": [80, 90]}"));
+        assert!(json.contains("\"name\": \"odd,\\\"even\\\"\""));
+        assert_eq!(super::json(&[]), "[]\n");
+    }
+
+    #[test]
+    fn markdown_report() {
+        let markdown = markdown(&measurements());
+        assert_eq!(markdown.lines().count(), 6);
+        assert!(markdown.contains("| comb/default | random | 100 | 1.51µs | 1.5µs | - | - |"));
+        assert!(markdown.contains("| 1 | 800 |"));
+    }
+
+    #[test]
+    fn html_report() {
+        let html = html(&measurements());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("random: time vs n (log-log)"));
+        assert_eq!(html.matches("<polyline").count(), 6);
+        assert!(html.contains("odd,&quot;even&quot;/default"));
+        assert!(!html.contains("NaN"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::{fmt, sync::atomic::AtomicUsize, time::Duration};
+
+use crate::{
+    Sorter,
+    dataset::{Distribution, Rng},
+};
+
+use super::{
+    Benchmarker, Measurement,
+    complexity::{Counted, count_comparisons},
+};
+
+/// What the [`Tuner`] minimizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Objective {
+    /// Total number of comparisons, which is exactly reproducible from the seed.
+    #[default]
+    Comparisons,
+    /// Sum of the median durations over every input.
+    Time,
+}
+
+/// [`Tuner`]
+///
+/// Searches the parameter space of a configurable sorter: every candidate parameter builds a
+/// sorter, which is run by the [`Benchmarker`] on its inputs and has its comparisons counted on
+/// the same inputs. The best candidate minimizes the [`Objective`].
+///
+/// ```
+/// use maniac::{
+///     CombSorter,
+///     bench::{Benchmarker, tuner::{Tuner, linspace}},
+///     dataset::Distribution,
+/// };
+///
+/// let tuner = Tuner {
+///     benchmarker: Benchmarker {
+///         sizes: vec![100, 1_000],
+///         distributions: vec![Distribution::Random],
+///         samples: 1,
+///         ..Benchmarker::default()
+///     },
+///     ..Tuner::default()
+/// };
+/// let tuning = tuner.tune("comb", linspace(1.1, 1.7, 7), |&shrink_factor| {
+///     CombSorter::default()
+///         .with_shrink_factor(shrink_factor)
+///         .unwrap()
+/// });
+///
+/// let best = tuning.best().parameter;
+/// assert!((1.2..=1.4).contains(&best), "{tuning}");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Tuner {
+    /// Inputs and timing settings of the search.
+    pub benchmarker: Benchmarker,
+    /// What the best candidate minimizes.
+    pub objective: Objective,
+}
+
+/// One configuration tried by the [`Tuner`].
+#[derive(Debug, Clone)]
+pub struct Candidate<P> {
+    /// The parameter the sorter was built from.
+    pub parameter: P,
+    /// Timings of the sorter, with the parameter as variant name.
+    pub measurements: Vec<Measurement>,
+    /// Total number of comparisons over every input.
+    pub comparisons: usize,
+    /// Sum of the median durations over every input.
+    pub time: Duration,
+}
+
+/// Outcome of [`Tuner::tune`]: every candidate, best first.
+#[derive(Debug, Clone)]
+pub struct Tuning<P> {
+    /// Name the sorter was measured under.
+    pub name: String,
+    /// What the candidates are ranked by.
+    pub objective: Objective,
+    /// Every candidate, best first.
+    pub candidates: Vec<Candidate<P>>,
+}
+
+impl<P> Tuning<P> {
+    /// The best candidate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no candidate was tried.
+    #[must_use]
+    pub fn best(&self) -> &Candidate<P> {
+        self.candidates.first().expect("no candidate was tried")
+    }
+
+    /// Every measurement of every candidate, e.g. to save a [`Baseline`](super::Baseline) or
+    /// render a [report](super::report).
+    #[must_use]
+    pub fn measurements(&self) -> Vec<Measurement> {
+        self.candidates
+            .iter()
+            .flat_map(|candidate| candidate.measurements.iter().cloned())
+            .collect()
+    }
+}
+
+impl<P: fmt::Debug> fmt::Display for Tuning<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} tuned by {:?}:", self.name, self.objective)?;
+        for (rank, candidate) in self.candidates.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}. {:?}: {} comparisons, {:?}",
+                rank + 1,
+                candidate.parameter,
+                candidate.comparisons,
+                candidate.time
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Tuner {
+    /// Tries every parameter of `parameters`, building its sorter with `build`, and ranks them.
+    /// Ties keep the order of `parameters`.
+    pub fn tune<P, S>(
+        &self,
+        name: &str,
+        parameters: impl IntoIterator<Item = P>,
+        build: impl Fn(&P) -> S,
+    ) -> Tuning<P>
+    where
+        P: fmt::Debug,
+        S: Sorter<i64> + for<'a> Sorter<Counted<'a>> + Clone,
+    {
+        let inputs = self.inputs();
+        let mut candidates: Vec<Candidate<P>> = parameters
+            .into_iter()
+            .map(|parameter| {
+                let sorter = build(&parameter);
+                let variant = format!("{parameter:?}");
+                let measurements = self.benchmarker.measure(name, &variant, &sorter);
+
+                let counter = AtomicUsize::new(0);
+                let comparisons = inputs
+                    .iter()
+                    .map(|input| count_comparisons(sorter.clone(), input, &counter))
+                    .sum();
+                let time = measurements.iter().map(Measurement::median).sum();
+                Candidate {
+                    parameter,
+                    measurements,
+                    comparisons,
+                    time,
+                }
+            })
+            .collect();
+
+        match self.objective {
+            Objective::Comparisons => candidates.sort_by_key(|candidate| candidate.comparisons),
+            Objective::Time => candidates.sort_by_key(|candidate| candidate.time),
+        }
+        Tuning {
+            name: name.to_string(),
+            objective: self.objective,
+            candidates,
+        }
+    }
+
+    /// The inputs of the benchmarker, generated in the same order from the same seed.
+    fn inputs(&self) -> Vec<Vec<i64>> {
+        let mut rng = Rng::new(self.benchmarker.seed);
+        let distributions: &[Distribution] = &self.benchmarker.distributions;
+        distributions
+            .iter()
+            .flat_map(|distribution| {
+                self.benchmarker
+                    .sizes
+                    .iter()
+                    .map(|&len| distribution.generate(len, &mut rng))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// `steps` evenly spaced values from `start` to `end` included, e.g. candidate shrink factors.
+#[allow(clippy::cast_precision_loss)]
+pub fn linspace(start: f64, end: f64, steps: usize) -> impl Iterator<Item = f64> {
+    let step = if steps > 1 {
+        (end - start) / (steps - 1) as f64
+    } else {
+        0.0
+    };
+    (0..steps).map(move |index| start + step * index as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BubbleSorter, InsertionSorter, Optimization, Swap};
+
+    fn tuner(objective: Objective) -> Tuner {
+        Tuner {
+            benchmarker: Benchmarker {
+                sizes: vec![50, 200],
+                distributions: vec![Distribution::Random, Distribution::NearlySorted],
+                samples: 1,
+                ..Benchmarker::default()
+            },
+            objective,
+        }
+    }
+
+    #[test]
+    fn linspace_includes_both_ends() {
+        let values: Vec<f64> = linspace(1.0, 2.0, 5).collect();
+        assert_eq!(values, [1.0, 1.25, 1.5, 1.75, 2.0]);
+        assert_eq!(linspace(1.0, 2.0, 1).collect::<Vec<_>>(), [1.0]);
+        assert_eq!(linspace(1.0, 2.0, 0).count(), 0);
+    }
+
+    #[test]
+    fn comparisons_are_deterministic() {
+        let optimizations = [
+            Optimization::None,
+            Optimization::LenMinusOne,
+            Optimization::NewLen,
+        ];
+        let run = || {
+            tuner(Objective::Comparisons).tune("bubble", optimizations, |&optimization| {
+                BubbleSorter { optimization }
+            })
+        };
+        let (first, second) = (run(), run());
+        let counts = |tuning: &Tuning<Optimization>| -> Vec<(Optimization, usize)> {
+            tuning
+                .candidates
+                .iter()
+                .map(|candidate| (candidate.parameter, candidate.comparisons))
+                .collect()
+        };
+        assert_eq!(counts(&first), counts(&second));
+        assert_eq!(first.best().parameter, Optimization::NewLen);
+        assert_eq!(first.candidates[2].parameter, Optimization::None);
+    }
+
+    #[test]
+    fn measurements_carry_the_parameter() {
+        let tuning =
+            tuner(Objective::Time).tune("insertion", [Swap], |&method| InsertionSorter { method });
+        let measurements = tuning.measurements();
+        assert_eq!(measurements.len(), 4);
+        assert!(
+            measurements
+                .iter()
+                .all(|measurement| measurement.variant == "Swap")
+        );
+        assert!(
+            tuning
+                .to_string()
+                .starts_with("insertion tuned by Time:\n  1. Swap: ")
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::Sorter;
+use super::{Cancelled, ControlledSorter, SortControl, Sorter, StableSorter};
 
 /// [`BubbleSorter`]
 ///
@@ -22,17 +22,17 @@ use super::Sorter;
 /// | Best | Average | Worst | Space | In-place | Stable |
 /// | ---- | ------- | ----- | ----- | -------- | ------ |
 /// | *O(n)* (already sorted) | *O(n²)* | *O(n²)* | *O(1)* | Yes | Yes |
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct BubbleSorter {
     /// Strategy that decides how far the next pass needs to scan.
-    optimization: Optimization,
+    pub optimization: Optimization,
 }
 
 /// Pass-length optimisation strategies for [`BubbleSorter`].
 ///
 /// All variants keep bubble sort’s canonical behaviour (*stable*, *in-place*), but trade a bit of
 /// code complexity for fewer comparisons on average.
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Optimization {
     /// **Wikipedia optimisation** – after a pass, the slice from the last swap to the end is
     /// already sorted, so the next pass can stop *earlier*.
@@ -50,9 +50,23 @@ pub enum Optimization {
 
 impl<T: Ord> Sorter<T> for BubbleSorter {
     fn sort(self, slice: &mut [T]) {
-        let mut n = slice.len();
+        // Nothing cancels a default control.
+        let _ = self.sort_with(slice, &SortControl::new());
+    }
+}
+
+/// A pass bubbles the largest remaining element to its place.
+impl<T: Ord> ControlledSorter<T> for BubbleSorter {
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled> {
+        control.checkpoint(0)?;
+        let slice_len = slice.len();
+        let mut n = slice_len;
+        // Every pass moves the largest remaining element in place, so `slice_len` passes always
+        // suffice; the bound only stops `Optimization::None` looping on an inconsistent `Ord`.
+        let mut passes = 0;
 
-        while n > 1 {
+        while n > 1 && passes < slice_len {
+            passes += 1;
             let mut last_swap = 0;
             for i in 1..n {
                 if slice[i - 1] > slice[i] {
@@ -61,6 +75,7 @@ impl<T: Ord> Sorter<T> for BubbleSorter {
                 }
             }
 
+            control.checkpoint(passes)?;
             if last_swap == 0 {
                 break;
             }
@@ -71,47 +86,31 @@ impl<T: Ord> Sorter<T> for BubbleSorter {
                 Optimization::None => n,
             };
         }
+        Ok(())
     }
 }
 
+impl<T: Ord> StableSorter<T> for BubbleSorter {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{sorter_common_tests, sorter_stability_tests};
-
-    sorter_common_tests!(
-        bubble_common_new_len,
-        BubbleSorter {
-            optimization: Optimization::NewLen
-        }
-    );
-    sorter_common_tests!(
-        bubble_common_len_minus_one,
-        BubbleSorter {
-            optimization: Optimization::LenMinusOne
-        }
-    );
-    sorter_common_tests!(
-        bubble_common_no_optim,
-        BubbleSorter {
-            optimization: Optimization::None
-        }
-    );
+    use crate::sorter_tests;
 
-    sorter_stability_tests!(
-        bubble_stability_new_len,
+    sorter_tests!(
+        bubble_new_len,
         BubbleSorter {
             optimization: Optimization::NewLen
         }
     );
-    sorter_stability_tests!(
-        bubble_stability_len_minus_one,
+    sorter_tests!(
+        bubble_len_minus_one,
         BubbleSorter {
             optimization: Optimization::LenMinusOne
         }
     );
-    sorter_stability_tests!(
-        bubble_stability_no_optim,
+    sorter_tests!(
+        bubble_no_optim,
         BubbleSorter {
             optimization: Optimization::None
         }
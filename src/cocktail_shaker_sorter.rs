@@ -1,4 +1,4 @@
-use super::Sorter;
+use super::{Cancelled, ControlledSorter, SortControl, Sorter, StableSorter};
 
 /// [`CocktailShakerSorter`]
 ///
@@ -7,7 +7,7 @@ use super::Sorter;
 /// slice in **both directions** on each pass:
 ///
 /// 1.  A **left-to-right scan** “bubbles” the largest unsorted element to the end of the working
-/// interval.
+///     interval.
 /// 2.  A **right-to-left scan** then “sinks” the smallest element back to the front.
 ///
 /// After every pair of scans the working interval shrinks to the range `left..right` between the
@@ -21,41 +21,62 @@ use super::Sorter;
 /// | Best                  | Average | Worst | Space | In-place | Stable |
 /// |-----------------------|---------|-------|-------|----------|--------|
 /// | *O(n)* (already sorted) | *O(n²)* | *O(n²)* | *O(1)* | Yes | Yes |
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct CocktailShakerSorter;
 
 impl<T: Ord> Sorter<T> for CocktailShakerSorter {
     fn sort(self, slice: &mut [T]) {
+        // Nothing cancels a default control.
+        let _ = self.sort_with(slice, &SortControl::new());
+    }
+}
+
+/// A pass is a forward and a backward scan.
+impl<T: Ord> ControlledSorter<T> for CocktailShakerSorter {
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled> {
+        control.checkpoint(0)?;
         let slice_len = slice.len();
         let mut left = 1;
         let mut right = slice_len;
         let mut sorted = false;
+        let mut passes = 0;
 
         while !sorted {
             sorted = true;
+            let mut last_swap = right;
             for i in left..right {
                 if slice[i - 1] > slice[i] {
                     slice.swap(i - 1, i);
-                    right = i;
+                    last_swap = i;
                     sorted = false;
                 }
             }
+            right = last_swap;
+
+            // Everything before the last swap is in place, so every pass with a swap shrinks the
+            // range, which bounds the number of passes even for an inconsistent `Ord`.
+            let mut last_swap = left;
             for i in (left..right).rev() {
                 if slice[i - 1] > slice[i] {
                     slice.swap(i - 1, i);
-                    left = i;
-                    sorted = false
+                    last_swap = i + 1;
+                    sorted = false;
                 }
             }
+            left = last_swap;
+            passes += 1;
+            control.checkpoint(passes)?;
         }
+        Ok(())
     }
 }
 
+impl<T: Ord> StableSorter<T> for CocktailShakerSorter {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{sorter_common_tests, sorter_stability_tests};
+    use crate::sorter_tests;
 
-    sorter_common_tests!(cocktail_common, CocktailShakerSorter);
-    sorter_stability_tests!(cocktail_stability, CocktailShakerSorter);
+    sorter_tests!(cocktail, CocktailShakerSorter);
 }
@@ -1,4 +1,6 @@
-use super::Sorter;
+use core::{error::Error, fmt};
+
+use super::{Cancelled, ControlledSorter, InsertionSorter, SortControl, Sorter, Swap};
 
 /// [`CombSorter`]
 ///
@@ -13,6 +15,8 @@ use super::Sorter;
 /// page.
 ///
 /// Every time the algorithm finishes an iteration the gap is shrinked again by the same factor.
+/// The shrink factor is kept as an exact ratio of integers, so the gaps are exact whatever the
+/// length of the slice.
 ///
 /// One notable aspect of this algorithm is the "rule of 11" described as such on the [wikipedia
 /// page](https://en.wikipedia.org/wiki/Comb_sort)
@@ -20,45 +24,234 @@ use super::Sorter;
 /// > size of 11, rounding up gap sizes of 9 or 10 (reached by dividing gaps of 12, 13 or 14 by 1.3)
 /// > to 11. This eliminates turtles surviving until the final gap-1 pass.
 ///
+/// It is applied by default, and can be turned off with [`CombSorter::with_rule_of_11`]. Once the
+/// gap reaches 1, the slice is finished by bubble sort passes or by an insertion sort, see
+/// [`Finish`].
+///
+/// ```
+/// use maniac::{CombSorter, Finish, Sorter};
+///
+/// let sorter = CombSorter::default()
+///     .with_shrink_factor(1.25)?
+///     .with_rule_of_11(false)
+///     .with_finish(Finish::Insertion);
+///
+/// let mut items = vec![5, 3, 1, 4, 2];
+/// sorter.sort(&mut items);
+/// assert_eq!(items, vec![1, 2, 3, 4, 5]);
+///
+/// assert!(CombSorter::default().with_shrink_factor(1.0).is_err());
+/// # Ok::<(), maniac::InvalidShrinkFactor>(())
+/// ```
+///
 /// Complexity:
 /// |Best|Average|Worst|Space|In-place|
 /// |---|---|---|---|---|
 /// |*O(n log n)*|*O(n<sup>2</sup>)*|*O(n<sup>2</sup>)*|*1*|Yes|
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CombSorter {
-    // TODO: determine wether it should be public, or at least give a `with` constructor or builder
-    // pattern
-    /// The shrink factor is the factor by wich we divide the gap every new iteration. It has been
-    /// determined to reach its optimal value around 1.3
-    pub shrink_factor: f32,
+    // The shrink factor the gap is divided by after every pass, as `numerator / denominator` in
+    // lowest terms.
+    numerator: u64,
+    denominator: u64,
+    rule_of_11: bool,
+    finish: Finish,
+}
+
+/// How the [`CombSorter`] finishes the slice once the gap reaches 1.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Finish {
+    /// Bubble sort passes with a gap of 1, until one of them swaps nothing.
+    #[default]
+    Bubble,
+    /// An insertion sort, which costs a single pass on the nearly sorted slice left by the larger
+    /// gaps.
+    Insertion,
 }
 
+/// Error returned when building a [`CombSorter`] with a shrink factor that would not shrink the
+/// gap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InvalidShrinkFactor {
+    /// The shrink factor is not greater than 1, or not a number.
+    NotGreaterThanOne(f64),
+    /// The shrink ratio has a zero denominator.
+    ZeroDenominator,
+}
+
+impl fmt::Display for InvalidShrinkFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotGreaterThanOne(factor) => {
+                write!(
+                    f,
+                    "invalid shrink factor {factor}: it must be greater than 1"
+                )
+            }
+            Self::ZeroDenominator => f.write_str("invalid shrink ratio: its denominator is 0"),
+        }
+    }
+}
+
+impl Error for InvalidShrinkFactor {}
+
 impl Default for CombSorter {
     fn default() -> Self {
-        Self { shrink_factor: 1.3 }
+        Self {
+            numerator: 13,
+            denominator: 10,
+            rule_of_11: true,
+            finish: Finish::default(),
+        }
     }
 }
 
-// TODO: arrange for handling of precision loss when casting from usize to f32. This is relevant for
-// very large arrays.
-#[allow(
-    clippy::cast_precision_loss,
-    clippy::cast_possible_truncation,
-    clippy::cast_sign_loss
-)]
+impl CombSorter {
+    /// Denominator of the ratio a shrink factor given as a float is rounded to.
+    const FLOAT_DENOMINATOR: u64 = 1_000_000;
+
+    /// Returns the sorter with a shrink factor of `numerator / denominator`, exactly.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `denominator` is 0, or if the ratio is not greater than 1, which would never
+    /// shrink the gap.
+    pub fn with_shrink_ratio(
+        self,
+        numerator: u64,
+        denominator: u64,
+    ) -> Result<Self, InvalidShrinkFactor> {
+        if denominator == 0 {
+            return Err(InvalidShrinkFactor::ZeroDenominator);
+        }
+        if numerator <= denominator {
+            #[allow(clippy::cast_precision_loss)]
+            return Err(InvalidShrinkFactor::NotGreaterThanOne(
+                numerator as f64 / denominator as f64,
+            ));
+        }
+        let divisor = gcd(numerator, denominator);
+        Ok(Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+            ..self
+        })
+    }
+
+    /// Returns the sorter with a shrink factor of `factor`, rounded to a ratio over a million.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `factor` is NaN or not greater than 1 once rounded, which would never shrink
+    /// the gap.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn with_shrink_factor(self, factor: f64) -> Result<Self, InvalidShrinkFactor> {
+        // The cast saturates, so huge factors stay huge: they go straight to a gap of 1. Adding a
+        // half before truncating rounds the positive factors, without `f64::round` from `std`.
+        let numerator = (factor * Self::FLOAT_DENOMINATOR as f64 + 0.5) as u64;
+        if factor.is_nan() || numerator <= Self::FLOAT_DENOMINATOR {
+            return Err(InvalidShrinkFactor::NotGreaterThanOne(factor));
+        }
+        self.with_shrink_ratio(numerator, Self::FLOAT_DENOMINATOR)
+    }
+
+    /// Returns the sorter with the rule of 11 applied or not.
+    #[must_use]
+    pub fn with_rule_of_11(self, rule_of_11: bool) -> Self {
+        Self { rule_of_11, ..self }
+    }
+
+    /// Returns the sorter finishing the slice with `finish`.
+    #[must_use]
+    pub fn with_finish(self, finish: Finish) -> Self {
+        Self { finish, ..self }
+    }
+
+    /// The shrink factor, as a float.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn shrink_factor(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+
+    /// The shrink factor, as an exact `(numerator, denominator)` ratio in lowest terms.
+    #[must_use]
+    pub fn shrink_ratio(self) -> (u64, u64) {
+        (self.numerator, self.denominator)
+    }
+
+    /// Whether gaps of 9 and 10 are rounded up to 11.
+    #[must_use]
+    pub fn rule_of_11(self) -> bool {
+        self.rule_of_11
+    }
+
+    /// How the slice is finished once the gap reaches 1.
+    #[must_use]
+    pub fn finish(self) -> Finish {
+        self.finish
+    }
+
+    /// Returns the gap following `gap`, or `1` once the final passes are reached. The gap
+    /// strictly decreases until then, since the shrink factor is greater than 1.
+    #[allow(clippy::cast_possible_truncation)]
+    pub(crate) fn next_gap(self, gap: usize) -> usize {
+        // `gap * denominator / numerator < gap` fits in a usize, only the product needs 128 bits.
+        let shrunk =
+            (gap as u128 * u128::from(self.denominator) / u128::from(self.numerator)) as usize;
+        match shrunk {
+            0 | 1 => 1,
+            // The aforementioned rule of 11, only coming from larger gaps: from 11 itself, the
+            // gap would stay at 11 forever.
+            9 | 10 if gap > 11 && self.rule_of_11 => 11,
+            gap => gap,
+        }
+    }
+}
+
+/// Greatest common divisor of `a` and `b`.
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
 impl<T: Ord> Sorter<T> for CombSorter {
     fn sort(self, slice: &mut [T]) {
+        // Nothing cancels a default control.
+        let _ = self.sort_with(slice, &SortControl::new());
+    }
+}
+
+/// A pass compares every pair of elements one gap apart. The final insertion sort of
+/// [`Finish::Insertion`] counts as one pass.
+impl<T: Ord> ControlledSorter<T> for CombSorter {
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled> {
+        control.checkpoint(0)?;
         let slice_len = slice.len();
         let mut gap = slice_len;
         let mut sorted = false;
+        let mut passes = 0;
+        // Passes with a gap of 1 are bubble sort passes, `slice_len` of them sort any input: the
+        // bound only matters for an inconsistent `Ord`.
+        let mut final_passes = 0;
         while !sorted {
-            gap = (gap as f32 / self.shrink_factor).floor() as usize;
-            if gap <= 1 {
-                gap = 1;
+            gap = self.next_gap(gap);
+            if gap == 1 {
+                if self.finish == Finish::Insertion {
+                    InsertionSorter { method: Swap }.sort(slice);
+                    return control.checkpoint(passes + 1);
+                }
+                if final_passes == slice_len {
+                    break;
+                }
+                final_passes += 1;
                 sorted = true;
-            } else if gap == 9 | 10 {
-                // The aforementioned rule of 11
-                gap = 11;
             }
             let mut iteration = 0;
             while gap + iteration < slice_len {
@@ -68,14 +261,101 @@ impl<T: Ord> Sorter<T> for CombSorter {
                 }
                 iteration += 1;
             }
+            passes += 1;
+            control.checkpoint(passes)?;
         }
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sorter_common_tests;
+    use crate::sorter_tests;
+
+    sorter_tests!(comb, CombSorter::default());
+    sorter_tests!(comb_plain, CombSorter::default().with_rule_of_11(false));
+    sorter_tests!(
+        comb_final_insertion,
+        CombSorter::default().with_finish(Finish::Insertion)
+    );
+    sorter_tests!(
+        comb_plain_final_insertion,
+        CombSorter::default()
+            .with_rule_of_11(false)
+            .with_finish(Finish::Insertion)
+    );
+    sorter_tests!(
+        comb_slow_shrink,
+        CombSorter::default().with_shrink_ratio(11, 10).unwrap()
+    );
+
+    #[test]
+    fn rule_of_11() {
+        let sorter = CombSorter::default();
+        for gap in [12, 13, 14] {
+            assert_eq!(sorter.next_gap(gap), 11);
+        }
+        assert_eq!(sorter.next_gap(11), 8);
+        assert_eq!(sorter.next_gap(2), 1);
+        assert_eq!(sorter.next_gap(0), 1);
 
-    sorter_common_tests!(comb_common_tests, CombSorter { shrink_factor: 1.3 });
+        let plain = sorter.with_rule_of_11(false);
+        assert_eq!(plain.next_gap(13), 10);
+    }
+
+    #[test]
+    fn gaps_always_shrink() {
+        // Shrink factors below 11/9 used to cycle between 11 and the rule of 11.
+        for (numerator, denominator) in [(11, 10), (6, 5), (13, 10), (2, 1), (1_000_001, 1_000_000)]
+        {
+            let sorter = CombSorter::default()
+                .with_shrink_ratio(numerator, denominator)
+                .unwrap();
+            for gap in 2..200 {
+                assert!(
+                    sorter.next_gap(gap) < gap,
+                    "{numerator}/{denominator}: {gap}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn gaps_are_exact_for_huge_slices() {
+        let sorter = CombSorter::default();
+        // 2^40 / 1.3 is not representable in an f32.
+        let gap = 1 << 40;
+        assert_eq!(sorter.next_gap(gap), 845_778_175_212);
+        assert_eq!(
+            sorter.next_gap(usize::MAX) as u128,
+            usize::MAX as u128 * 10 / 13
+        );
+    }
+
+    #[test]
+    fn validates_shrink_factors() {
+        let sorter = CombSorter::default();
+        for factor in [1.0, 0.5, -2.0, f64::NAN, f64::NEG_INFINITY, 1.000_000_1] {
+            assert!(sorter.with_shrink_factor(factor).is_err(), "{factor}");
+        }
+        assert!(sorter.with_shrink_ratio(1, 0).is_err());
+        assert!(sorter.with_shrink_ratio(5, 5).is_err());
+
+        assert_eq!(sorter.with_shrink_factor(1.3).unwrap(), sorter);
+        assert_eq!(
+            sorter.with_shrink_factor(1.25).unwrap().shrink_ratio(),
+            (5, 4)
+        );
+        let huge = sorter.with_shrink_factor(f64::INFINITY).unwrap();
+        assert_eq!(huge.next_gap(1 << 20), 1);
+        assert_eq!(
+            sorter.with_shrink_factor(0.5).unwrap_err().to_string(),
+            "invalid shrink factor 0.5: it must be greater than 1"
+        );
+        assert_eq!(
+            sorter.with_shrink_ratio(1, 0).unwrap_err().to_string(),
+            "invalid shrink ratio: its denominator is 0"
+        );
+    }
 }
@@ -0,0 +1,254 @@
+use core::{
+    error::Error,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use super::Sorter;
+
+/// [`ControlledSorter`]
+///
+/// A sorter whose sorts can report their progress and be cancelled through a [`SortControl`],
+/// for long-running sorts driven by a user interface. Progress is reported, and cancellation
+/// checked, after every *pass* of the algorithm, whose meaning each implementation documents.
+///
+/// Cancelled sorts stop at the end of the current pass: the slice is then a permutation of the
+/// original, partially sorted.
+///
+/// ```
+/// use maniac::{BubbleSorter, CancelToken, ControlledSorter, SortControl};
+/// use std::thread;
+///
+/// let stop = CancelToken::new();
+/// let mut items: Vec<u32> = (0..10_000).rev().collect();
+/// thread::scope(|scope| {
+///     let sort = scope.spawn(|| {
+///         let control = SortControl::new().with_cancel(&stop);
+///         BubbleSorter::default().sort_with(&mut items, &control)
+///     });
+///     // The stop button of the user interface.
+///     stop.cancel();
+///     let _finished_or_cancelled = sort.join().unwrap();
+/// });
+///
+/// // Whether the sort finished or not, no element was lost.
+/// items.sort();
+/// assert!(items.iter().copied().eq(0..10_000));
+/// ```
+pub trait ControlledSorter<T>: Sorter<T>
+where
+    T: Ord,
+{
+    /// Sorts `slice` like [`Sorter::sort`], reporting to `control` after every pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the cancel token of `control` was cancelled before the sort
+    /// finished.
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled>;
+}
+
+/// Flag cancelling the sorts that were given it through a [`SortControl`], typically from
+/// another thread.
+#[derive(Debug, Default)]
+pub struct CancelToken(AtomicBool);
+
+impl CancelToken {
+    /// Returns a token that is not cancelled.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(AtomicBool::new(false))
+    }
+
+    /// Cancels the sorts controlled by this token, at the end of their current pass.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) was called.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress of a sort, reported after every pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Progress {
+    /// Number of passes completed so far, 0 when the sort starts.
+    pub passes: usize,
+}
+
+/// [`SortControl`]
+///
+/// What a [`ControlledSorter`] reports its progress to and checks for cancellation: an optional
+/// progress callback and an optional [`CancelToken`]. The default control does neither.
+///
+/// ```
+/// use maniac::{ControlledSorter, GnomeSorter, Progress, SortControl};
+/// use std::cell::Cell;
+///
+/// let passes = Cell::new(0);
+/// let report = |progress: Progress| passes.set(progress.passes);
+/// let control = SortControl::new().with_progress(&report);
+///
+/// let mut items = [4, 1, 3, 2];
+/// GnomeSorter.sort_with(&mut items, &control)?;
+/// // One pass per element inserted in the sorted prefix.
+/// assert_eq!(passes.get(), 3);
+/// # Ok::<(), maniac::Cancelled>(())
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct SortControl<'a> {
+    cancel: Option<&'a CancelToken>,
+    progress: Option<&'a dyn Fn(Progress)>,
+}
+
+impl<'a> SortControl<'a> {
+    /// Returns a control that reports to nothing and is never cancelled.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            cancel: None,
+            progress: None,
+        }
+    }
+
+    /// Returns the control, cancelled by `token`.
+    #[must_use]
+    pub const fn with_cancel(self, token: &'a CancelToken) -> Self {
+        Self {
+            cancel: Some(token),
+            ..self
+        }
+    }
+
+    /// Returns the control, reporting every pass to `progress`.
+    #[must_use]
+    pub const fn with_progress(self, progress: &'a dyn Fn(Progress)) -> Self {
+        Self {
+            progress: Some(progress),
+            ..self
+        }
+    }
+
+    /// To be called by sorters once they have completed `passes` passes, and with 0 when they
+    /// start: returns [`Cancelled`] if the sort must stop, reports the progress otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Cancelled`] if the cancel token of the control was cancelled.
+    pub fn checkpoint(&self, passes: usize) -> Result<(), Cancelled> {
+        if self.cancel.is_some_and(CancelToken::is_cancelled) {
+            return Err(Cancelled);
+        }
+        if let Some(progress) = self.progress {
+            progress(Progress { passes });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for SortControl<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortControl")
+            .field("cancel", &self.cancel)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// Error returned by [`ControlledSorter::sort_with`] when the sort was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the sort was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BubbleSorter, CocktailShakerSorter, CombSorter, Finish, GnomeSorter, InsertionSorter,
+        OddEvenSorter, ParallelPhases, RotateUnstable, Swap, dataset::Rng,
+    };
+    use std::cell::{Cell, RefCell};
+
+    /// Sorts a random input with `sorter`, checking that it reports consecutive passes, then
+    /// cancels it after every possible number of passes.
+    fn assert_controlled<S: ControlledSorter<u32> + Copy>(sorter: S) {
+        let mut rng = Rng::new(5);
+        let input: Vec<u32> = (0..200)
+            .map(|_| u32::try_from(rng.below(50)).unwrap())
+            .collect();
+        let mut expected = input.clone();
+        expected.sort_unstable();
+
+        let reported = RefCell::new(Vec::new());
+        let report = |progress: Progress| reported.borrow_mut().push(progress.passes);
+        let mut items = input.clone();
+        sorter
+            .sort_with(&mut items, &SortControl::new().with_progress(&report))
+            .unwrap();
+        assert_eq!(items, expected);
+        let reported = reported.into_inner();
+        assert_eq!(reported, (0..reported.len()).collect::<Vec<_>>());
+
+        // Cancelling after the last pass is too late.
+        for cancel_after in 0..reported.len() - 1 {
+            let token = CancelToken::new();
+            let passes = Cell::new(0);
+            let report = |progress: Progress| {
+                passes.set(progress.passes);
+                if progress.passes == cancel_after {
+                    token.cancel();
+                }
+            };
+            let control = SortControl::new()
+                .with_cancel(&token)
+                .with_progress(&report);
+            let mut items = input.clone();
+            assert_eq!(sorter.sort_with(&mut items, &control), Err(Cancelled));
+            assert_eq!(passes.get(), cancel_after);
+            items.sort_unstable();
+            assert_eq!(items, expected, "cancelled after {cancel_after} passes");
+        }
+    }
+
+    #[test]
+    fn sorters_report_passes_and_stop_when_cancelled() {
+        assert_controlled(BubbleSorter::default());
+        assert_controlled(CocktailShakerSorter);
+        assert_controlled(CombSorter::default());
+        assert_controlled(CombSorter::default().with_finish(Finish::Insertion));
+        assert_controlled(GnomeSorter);
+        assert_controlled(InsertionSorter::default());
+        assert_controlled(InsertionSorter {
+            method: RotateUnstable,
+        });
+        assert_controlled(InsertionSorter { method: Swap });
+        assert_controlled(OddEvenSorter::default());
+        assert_controlled(OddEvenSorter {
+            phases: ParallelPhases,
+        });
+    }
+
+    #[test]
+    fn cancelled_before_starting() {
+        let token = CancelToken::new();
+        token.cancel();
+        let mut items = [3, 1, 2];
+        let control = SortControl::new().with_cancel(&token);
+        assert_eq!(
+            BubbleSorter::default().sort_with(&mut items, &control),
+            Err(Cancelled)
+        );
+        assert_eq!(items, [3, 1, 2]);
+    }
+}
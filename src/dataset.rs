@@ -0,0 +1,185 @@
+use alloc::{vec, vec::Vec};
+
+/// [`Rng`]
+///
+/// Small, fast and deterministic pseudo-random generator
+/// ([SplitMix64](https://prng.di.unimi.it/splitmix64.c)). It is **not** suitable for cryptography;
+/// it only exists so that test and benchmark inputs can be reproduced from a seed without any
+/// dependency.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a generator from a seed. The same seed always yields the same sequence.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in `0..bound`, or `0` if `bound` is `0`.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        // Lemire's multiply-shift reduction, the bias is negligible for test data.
+        ((u128::from(self.next_u64()) * bound as u128) >> 64) as usize
+    }
+
+    /// Returns `true` with a probability of `1 / n`.
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    /// Shuffles `slice` uniformly (Fisher–Yates).
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        for i in (1..slice.len()).rev() {
+            slice.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Shape of a generated input, see [`Distribution::generate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Distribution {
+    /// Uniformly random values over the whole `i64` range.
+    Random,
+    /// Already sorted in ascending order.
+    Sorted,
+    /// Sorted in descending order.
+    Reversed,
+    /// Sorted, then about 2% of the elements swapped with a random partner.
+    NearlySorted,
+    /// Random values drawn from only 8 distinct keys.
+    FewUnique,
+    /// The same value repeated.
+    AllEqual,
+    /// Ascending first half followed by a descending second half.
+    OrganPipe,
+    /// Ascending runs of about `√n` elements, one after the other.
+    Sawtooth,
+}
+
+impl Distribution {
+    /// Every distribution, in declaration order.
+    pub const ALL: [Self; 8] = [
+        Self::Random,
+        Self::Sorted,
+        Self::Reversed,
+        Self::NearlySorted,
+        Self::FewUnique,
+        Self::AllEqual,
+        Self::OrganPipe,
+        Self::Sawtooth,
+    ];
+
+    /// Short lowercase name of the distribution, e.g. `"nearly_sorted"`.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Sorted => "sorted",
+            Self::Reversed => "reversed",
+            Self::NearlySorted => "nearly_sorted",
+            Self::FewUnique => "few_unique",
+            Self::AllEqual => "all_equal",
+            Self::OrganPipe => "organ_pipe",
+            Self::Sawtooth => "sawtooth",
+        }
+    }
+
+    /// Distribution with the given [`name`](Distribution::name), if any.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|distribution| distribution.name() == name)
+    }
+
+    /// Generates `len` values following this distribution.
+    #[allow(clippy::cast_possible_wrap)]
+    pub fn generate(self, len: usize, rng: &mut Rng) -> Vec<i64> {
+        // Lengths fit in an i64 on every supported target.
+        let ascending = (0..len).map(|i| i as i64);
+        match self {
+            Self::Random => (0..len).map(|_| rng.next_u64() as i64).collect(),
+            Self::Sorted => ascending.collect(),
+            Self::Reversed => ascending.rev().collect(),
+            Self::NearlySorted => {
+                let mut values: Vec<i64> = ascending.collect();
+                for _ in 0..len.div_ceil(50) {
+                    let (a, b) = (rng.below(len), rng.below(len));
+                    values.swap(a, b);
+                }
+                values
+            }
+            Self::FewUnique => (0..len).map(|_| rng.below(8) as i64).collect(),
+            Self::AllEqual => vec![42; len],
+            Self::OrganPipe => (0..len).map(|i| i.min(len - 1 - i) as i64).collect(),
+            Self::Sawtooth => {
+                let run = len.isqrt().max(1);
+                (0..len).map(|i| (i % run) as i64).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = Rng::new(7);
+        let mut b = Rng::new(7);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn below_stays_in_bounds() {
+        let mut rng = Rng::new(1);
+        assert_eq!(rng.below(0), 0);
+        for bound in 1..100 {
+            assert!(rng.below(bound) < bound);
+        }
+    }
+
+    #[test]
+    fn shapes() {
+        let mut rng = Rng::new(3);
+        for distribution in Distribution::ALL {
+            for len in [0, 1, 2, 17, 100] {
+                assert_eq!(distribution.generate(len, &mut rng).len(), len);
+            }
+        }
+        assert!(Distribution::Sorted.generate(10, &mut rng).is_sorted());
+        for distribution in Distribution::ALL {
+            assert_eq!(
+                Distribution::from_name(distribution.name()),
+                Some(distribution)
+            );
+        }
+        assert_eq!(Distribution::from_name("shuffled"), None);
+        assert_eq!(
+            Distribution::OrganPipe.generate(6, &mut rng),
+            vec![0, 1, 2, 2, 1, 0]
+        );
+        assert_eq!(
+            Distribution::Sawtooth.generate(6, &mut rng),
+            vec![0, 1, 0, 1, 0, 1]
+        );
+    }
+}
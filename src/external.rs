@@ -0,0 +1,483 @@
+use std::{
+    cmp, env, fs,
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{Sorter, kmerge_by};
+
+/// Record formats of the sorted data: lines, fixed-width binary and length-prefixed binary.
+pub mod codec;
+pub use codec::{Codec, FixedWidth, LengthPrefixed, Lines};
+
+/// [`ExternalSorter`]
+///
+/// Sorts data that does not fit in memory. The records of the input are read into chunks of at
+/// most [`memory_budget`](ExternalSorter::with_memory_budget) bytes, each chunk is sorted by the
+/// in-memory `sorter` and spilled to a temporary file as a sorted run, and the runs are
+/// [k-way merged](crate::KMerge) into the output, at most
+/// [`fan_in`](ExternalSorter::with_fan_in) at a time. An input that fits in a single chunk is
+/// sorted without touching the disk.
+///
+/// The merge takes the records comparing equal in the order of their runs, so a stable `sorter`
+/// makes a stable external sort. The temporary files are removed once merged, even on errors.
+///
+/// ```
+/// use maniac::{
+///     InsertionSorter, Swap,
+///     external::{ExternalSorter, Lines},
+/// };
+///
+/// let input = "pear\napple\nfig\nbanana\n";
+/// let mut output = Vec::new();
+///
+/// let stats = ExternalSorter::new(InsertionSorter { method: Swap }, Lines)
+///     .with_memory_budget(64)
+///     .sort(input.as_bytes(), &mut output)?;
+///
+/// assert_eq!(output, b"apple\nbanana\nfig\npear\n");
+/// assert_eq!(stats.records, 4);
+/// assert_eq!(stats.runs, 2);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExternalSorter<S, C> {
+    sorter: S,
+    codec: C,
+    memory_budget: usize,
+    fan_in: usize,
+    temp_dir: PathBuf,
+}
+
+/// What an [`ExternalSorter::sort`] did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExternalStats {
+    /// Number of records sorted.
+    pub records: usize,
+    /// Number of sorted runs spilled to disk, 0 if the input fit in memory.
+    pub runs: usize,
+    /// Number of merge passes over the data, the last one writing the output.
+    pub merge_passes: usize,
+}
+
+/// A sorted run in a temporary file, removed on drop.
+#[derive(Debug)]
+struct Run {
+    path: PathBuf,
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        // Nothing sensible to do if it fails, the file is only garbage in the temporary directory.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The records of a run, ending after the first error.
+struct Records<'a, C, R> {
+    codec: &'a C,
+    reader: R,
+    failed: bool,
+}
+
+impl<C: Codec, R: BufRead> Iterator for Records<'_, C, R> {
+    type Item = io::Result<C::Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let record = self.codec.read_record(&mut self.reader);
+        self.failed = record.is_err();
+        record.transpose()
+    }
+}
+
+impl<S, C> ExternalSorter<S, C>
+where
+    S: Sorter<C::Record> + Clone,
+    C: Codec,
+{
+    /// Default memory budget, 64 MiB.
+    pub const DEFAULT_MEMORY_BUDGET: usize = 64 << 20;
+    /// Default number of runs merged at once.
+    pub const DEFAULT_FAN_IN: usize = 16;
+
+    /// Returns an external sorter sorting the chunks with `sorter` and reading and writing the
+    /// records with `codec`, with the default memory budget and fan-in, spilling to the
+    /// temporary directory of the system.
+    pub fn new(sorter: S, codec: C) -> Self {
+        Self {
+            sorter,
+            codec,
+            memory_budget: Self::DEFAULT_MEMORY_BUDGET,
+            fan_in: Self::DEFAULT_FAN_IN,
+            temp_dir: env::temp_dir(),
+        }
+    }
+
+    /// Returns the sorter keeping at most `memory_budget` bytes of records in memory, as counted
+    /// by [`Codec::memory_size`]. A chunk always holds at least one record, however large.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `memory_budget` is 0.
+    #[must_use]
+    pub fn with_memory_budget(self, memory_budget: usize) -> Self {
+        assert!(memory_budget > 0, "the memory budget must not be 0");
+        Self {
+            memory_budget,
+            ..self
+        }
+    }
+
+    /// Returns the sorter merging at most `fan_in` runs at once. More runs are first merged
+    /// into fewer, longer runs, in as many passes as needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fan_in` is less than 2.
+    #[must_use]
+    pub fn with_fan_in(self, fan_in: usize) -> Self {
+        assert!(fan_in >= 2, "the fan-in must be at least 2, got {fan_in}");
+        Self { fan_in, ..self }
+    }
+
+    /// Returns the sorter spilling its runs to `temp_dir`.
+    #[must_use]
+    pub fn with_temp_dir(self, temp_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            temp_dir: temp_dir.into(),
+            ..self
+        }
+    }
+
+    /// Sorts the records of `input` into `output`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `input`, `output`, the codec and the temporary files. The output
+    /// may then hold part of the sorted records.
+    pub fn sort(&self, input: impl Read, output: impl Write) -> io::Result<ExternalStats> {
+        let mut input = BufReader::new(input);
+        let mut output = BufWriter::new(output);
+        let mut stats = ExternalStats::default();
+
+        let mut runs = Vec::new();
+        let mut chunk = Vec::new();
+        let mut chunk_size = 0;
+        while let Some(record) = self.codec.read_record(&mut input)? {
+            let size = self.codec.memory_size(&record);
+            if !chunk.is_empty() && chunk_size + size > self.memory_budget {
+                runs.push(self.spill(&mut chunk)?);
+                chunk_size = 0;
+            }
+            chunk_size += size;
+            chunk.push(record);
+            stats.records += 1;
+        }
+
+        if runs.is_empty() {
+            self.sorter.clone().sort(&mut chunk);
+            for record in &chunk {
+                self.codec.write_record(record, &mut output)?;
+            }
+        } else {
+            if !chunk.is_empty() {
+                runs.push(self.spill(&mut chunk)?);
+            }
+            drop(chunk);
+            stats.runs = runs.len();
+
+            while runs.len() > self.fan_in {
+                runs = runs
+                    .chunks(self.fan_in)
+                    .map(|group| {
+                        let (run, file) = self.create_run()?;
+                        let mut writer = BufWriter::new(file);
+                        self.merge(group, &mut writer)?;
+                        writer.flush()?;
+                        Ok(run)
+                    })
+                    .collect::<io::Result<_>>()?;
+                stats.merge_passes += 1;
+            }
+            self.merge(&runs, &mut output)?;
+            stats.merge_passes += 1;
+        }
+        output.flush()?;
+        Ok(stats)
+    }
+
+    /// Sorts `chunk` and writes it to a new run, leaving `chunk` empty.
+    fn spill(&self, chunk: &mut Vec<C::Record>) -> io::Result<Run> {
+        self.sorter.clone().sort(chunk);
+        let (run, file) = self.create_run()?;
+        let mut writer = BufWriter::new(file);
+        for record in chunk.drain(..) {
+            self.codec.write_record(&record, &mut writer)?;
+        }
+        writer.flush()?;
+        Ok(run)
+    }
+
+    /// Creates a new empty run in the temporary directory.
+    fn create_run(&self) -> io::Result<(Run, fs::File)> {
+        static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+        loop {
+            let path = self.temp_dir.join(format!(
+                "maniac-{}-{}.run",
+                process::id(),
+                NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+            ));
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(file) => return Ok((Run { path }, file)),
+                // Left over by a previous process with the same id.
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Merges the sorted `runs` into `output`.
+    fn merge(&self, runs: &[Run], output: &mut impl Write) -> io::Result<()> {
+        // The memory budget holds the read buffers instead of the records during the merge.
+        let capacity = (self.memory_budget / runs.len()).max(1 << 10);
+        let runs = runs
+            .iter()
+            .map(|run| {
+                Ok(Records {
+                    codec: &self.codec,
+                    reader: BufReader::with_capacity(capacity, fs::File::open(&run.path)?),
+                    failed: false,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        // Errors come first, to stop the merge as soon as a run fails. Equal records leave in the
+        // order of their runs, so the merge is stable.
+        let merged = kmerge_by(runs, |a, b| match (a, b) {
+            (Ok(a), Ok(b)) => a.cmp(b),
+            (Err(_), Err(_)) => cmp::Ordering::Equal,
+            (Err(_), Ok(_)) => cmp::Ordering::Less,
+            (Ok(_), Err(_)) => cmp::Ordering::Greater,
+        });
+        for record in merged {
+            self.codec.write_record(&record?, output)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InsertionSorter, StandardSorter, Swap, dataset::Rng};
+
+    /// A fresh temporary directory, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("maniac-test-{}-{name}", process::id()));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn is_empty(&self) -> bool {
+            fs::read_dir(&self.0).unwrap().next().is_none()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn random_lines(len: usize) -> Vec<Vec<u8>> {
+        let mut rng = Rng::new(42);
+        (0..len)
+            .map(|_| format!("{:x}", rng.next_u64() % 100_000).into_bytes())
+            .collect()
+    }
+
+    fn encode<C: Codec>(codec: &C, records: &[C::Record]) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        for record in records {
+            codec.write_record(record, &mut encoded).unwrap();
+        }
+        encoded
+    }
+
+    #[test]
+    fn small_inputs_stay_in_memory() {
+        let dir = TempDir::new("small");
+        let records = random_lines(100);
+        let mut output = Vec::new();
+        let stats = ExternalSorter::new(StandardSorter, Lines)
+            .with_temp_dir(&dir.0)
+            .sort(&encode(&Lines, &records)[..], &mut output)
+            .unwrap();
+
+        let mut expected = records;
+        expected.sort();
+        assert_eq!(output, encode(&Lines, &expected));
+        assert_eq!(
+            stats,
+            ExternalStats {
+                records: 100,
+                runs: 0,
+                merge_passes: 0,
+            }
+        );
+    }
+
+    fn spills_and_merges_in_several_passes<C: Codec<Record = Vec<u8>> + Copy>(codec: C) {
+        let dir = TempDir::new("passes");
+        let records = random_lines(1_000);
+        let mut output = Vec::new();
+        let stats = ExternalSorter::new(InsertionSorter { method: Swap }, codec)
+            .with_memory_budget(1 << 10)
+            .with_fan_in(3)
+            .with_temp_dir(&dir.0)
+            .sort(&encode(&codec, &records)[..], &mut output)
+            .unwrap();
+
+        let mut expected = records;
+        expected.sort();
+        assert_eq!(output, encode(&codec, &expected));
+        assert_eq!(stats.records, 1_000);
+        // A thousand records of about 30 bytes in memory, a KiB at a time.
+        assert!(stats.runs > 27, "{stats:?}");
+        let (mut runs, mut passes) = (stats.runs, 1);
+        while runs > 3 {
+            runs = runs.div_ceil(3);
+            passes += 1;
+        }
+        assert_eq!(stats.merge_passes, passes, "{stats:?}");
+        assert!(dir.is_empty());
+    }
+
+    #[test]
+    fn spills_lines() {
+        spills_and_merges_in_several_passes(Lines);
+    }
+
+    #[test]
+    fn spills_length_prefixed_records() {
+        spills_and_merges_in_several_passes(LengthPrefixed);
+    }
+
+    #[test]
+    fn sorts_with_boxed_registry_entries() {
+        let dir = TempDir::new("boxed");
+        let records = random_lines(1_000);
+        let mut expected = records.clone();
+        expected.sort();
+
+        for sorter in [
+            crate::registry::find("merge", "sequential").unwrap().sorter,
+            Box::new(
+                "sample(insertion):buckets=4"
+                    .parse::<crate::spec::Spec>()
+                    .unwrap(),
+            ),
+        ] {
+            let mut output = Vec::new();
+            let stats = ExternalSorter::new(sorter, Lines)
+                .with_memory_budget(1_000)
+                .with_temp_dir(&dir.0)
+                .sort(&encode(&Lines, &records)[..], &mut output)
+                .unwrap();
+            assert_eq!(output, encode(&Lines, &expected));
+            assert!(stats.runs > 1);
+        }
+    }
+
+    #[test]
+    fn sorts_fixed_width_records() {
+        let dir = TempDir::new("fixed");
+        let mut rng = Rng::new(7);
+        let records: Vec<Vec<u8>> = (0..500)
+            .map(|_| rng.next_u64().to_be_bytes().to_vec())
+            .collect();
+        let codec = FixedWidth { width: 8 };
+        let mut output = Vec::new();
+        let stats = ExternalSorter::new(StandardSorter, codec)
+            .with_memory_budget(1 << 12)
+            .with_temp_dir(&dir.0)
+            .sort(&encode(&codec, &records)[..], &mut output)
+            .unwrap();
+
+        let mut expected = records;
+        expected.sort();
+        assert_eq!(output, encode(&codec, &expected));
+        assert!(stats.runs > 1);
+        assert!(dir.is_empty());
+    }
+
+    #[test]
+    fn equal_records_keep_their_order_across_runs() {
+        // Sorted by their first byte only, the second one tells the input order.
+        #[derive(Debug, PartialEq, Eq)]
+        struct Keyed(Vec<u8>);
+        impl PartialOrd for Keyed {
+            fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for Keyed {
+            fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                self.0[0].cmp(&other.0[0])
+            }
+        }
+        struct KeyedCodec;
+        impl Codec for KeyedCodec {
+            type Record = Keyed;
+            fn read_record(&self, reader: &mut impl BufRead) -> io::Result<Option<Keyed>> {
+                Ok(FixedWidth { width: 2 }.read_record(reader)?.map(Keyed))
+            }
+            fn write_record(&self, record: &Keyed, writer: &mut impl Write) -> io::Result<()> {
+                FixedWidth { width: 2 }.write_record(&record.0, writer)
+            }
+            fn memory_size(&self, _: &Keyed) -> usize {
+                1
+            }
+        }
+
+        let dir = TempDir::new("stable");
+        let input: Vec<u8> = (0..200u8).flat_map(|index| [index % 3, index]).collect();
+        let mut output = Vec::new();
+        ExternalSorter::new(InsertionSorter { method: Swap }, KeyedCodec)
+            .with_memory_budget(7)
+            .with_fan_in(4)
+            .with_temp_dir(&dir.0)
+            .sort(&input[..], &mut output)
+            .unwrap();
+
+        let mut expected: Vec<[u8; 2]> = (0..200u8).map(|index| [index % 3, index]).collect();
+        expected.sort_by_key(|record| record[0]);
+        assert_eq!(output, expected.concat());
+    }
+
+    #[test]
+    fn errors_remove_the_runs() {
+        let dir = TempDir::new("errors");
+        let mut input = encode(&FixedWidth { width: 4 }, &vec![b"abcd".to_vec(); 100]);
+        input.pop();
+        let error = ExternalSorter::new(StandardSorter, FixedWidth { width: 4 })
+            .with_memory_budget(100)
+            .with_temp_dir(&dir.0)
+            .sort(&input[..], io::sink())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(dir.is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    io::{self, BufRead, Read, Write},
+    mem,
+};
+
+/// [`Codec`]
+///
+/// Reads and writes the records of an [`ExternalSorter`](super::ExternalSorter), both from its
+/// input and its temporary runs: whatever `write_record` writes, `read_record` must read back.
+pub trait Codec {
+    /// The sorted record.
+    type Record: Ord;
+
+    /// Reads the next record of `reader`, or `None` at the end of the input.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `reader`, or an [`io::ErrorKind::UnexpectedEof`] error if the input
+    /// ends in the middle of a record.
+    fn read_record(&self, reader: &mut impl BufRead) -> io::Result<Option<Self::Record>>;
+
+    /// Writes `record` to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of `writer`, or an [`io::ErrorKind::InvalidInput`] error if `record`
+    /// cannot be encoded.
+    fn write_record(&self, record: &Self::Record, writer: &mut impl Write) -> io::Result<()>;
+
+    /// Approximate number of bytes `record` takes in memory, counted against the memory budget.
+    fn memory_size(&self, record: &Self::Record) -> usize;
+}
+
+/// Newline terminated records, sorted bytewise without their `\n`. The last line of the input
+/// may miss its `\n`, it gets one in the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lines;
+
+impl Codec for Lines {
+    type Record = Vec<u8>;
+
+    fn read_record(&self, reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    fn write_record(&self, record: &Vec<u8>, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(record)?;
+        writer.write_all(b"\n")
+    }
+
+    fn memory_size(&self, record: &Vec<u8>) -> usize {
+        mem::size_of::<Vec<u8>>() + record.len()
+    }
+}
+
+/// Binary records of exactly `width` bytes, sorted bytewise. The width must not be 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedWidth {
+    /// Size of every record, in bytes.
+    pub width: usize,
+}
+
+impl Codec for FixedWidth {
+    type Record = Vec<u8>;
+
+    fn read_record(&self, reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+        if self.width == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "records of 0 bytes cannot be delimited",
+            ));
+        }
+        let mut record = vec![0; self.width];
+        Ok(read_exact_or_end(reader, &mut record)?.then_some(record))
+    }
+
+    fn write_record(&self, record: &Vec<u8>, writer: &mut impl Write) -> io::Result<()> {
+        if record.len() != self.width {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("record of {} bytes, expected {}", record.len(), self.width),
+            ));
+        }
+        writer.write_all(record)
+    }
+
+    fn memory_size(&self, record: &Vec<u8>) -> usize {
+        mem::size_of::<Vec<u8>>() + record.len()
+    }
+}
+
+/// Binary records preceded by their length as a little-endian `u32`, sorted bytewise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LengthPrefixed;
+
+impl Codec for LengthPrefixed {
+    type Record = Vec<u8>;
+
+    fn read_record(&self, reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+        let mut prefix = [0; 4];
+        if !read_exact_or_end(reader, &mut prefix)? {
+            return Ok(None);
+        }
+        // Read through `take` rather than into a buffer of the announced length, so that a
+        // corrupted prefix fails on the end of the input instead of allocating gigabytes.
+        let len = u64::from(u32::from_le_bytes(prefix));
+        let mut record = Vec::new();
+        if reader.by_ref().take(len).read_to_end(&mut record)? as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(Some(record))
+    }
+
+    fn write_record(&self, record: &Vec<u8>, writer: &mut impl Write) -> io::Result<()> {
+        let len = u32::try_from(record.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("record of {} bytes, the prefix is a u32", record.len()),
+            )
+        })?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(record)
+    }
+
+    fn memory_size(&self, record: &Vec<u8>) -> usize {
+        mem::size_of::<Vec<u8>>() + record.len()
+    }
+}
+
+/// Fills `buf` from `reader`, or returns `false` if `reader` is at its end. Ending in the middle
+/// of `buf` is an [`io::ErrorKind::UnexpectedEof`] error.
+fn read_exact_or_end(reader: &mut impl BufRead, buf: &mut [u8]) -> io::Result<bool> {
+    if reader.fill_buf()?.is_empty() {
+        return Ok(false);
+    }
+    reader.read_exact(buf)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<C: Codec>(codec: &C, records: &[C::Record]) -> Vec<C::Record> {
+        let mut encoded = Vec::new();
+        for record in records {
+            codec.write_record(record, &mut encoded).unwrap();
+        }
+        let mut reader = &encoded[..];
+        let mut decoded = Vec::new();
+        while let Some(record) = codec.read_record(&mut reader).unwrap() {
+            decoded.push(record);
+        }
+        decoded
+    }
+
+    #[test]
+    fn codecs_round_trip() {
+        let records = vec![b"pear".to_vec(), Vec::new(), b"fig\r".to_vec()];
+        assert_eq!(round_trip(&Lines, &records), records);
+        assert_eq!(round_trip(&LengthPrefixed, &records), records);
+
+        let records = vec![b"ab".to_vec(), b"\n\0".to_vec()];
+        assert_eq!(round_trip(&FixedWidth { width: 2 }, &records), records);
+    }
+
+    #[test]
+    fn last_line_may_miss_its_newline() {
+        let mut reader = &b"a\nb"[..];
+        assert_eq!(Lines.read_record(&mut reader).unwrap(), Some(b"a".to_vec()));
+        assert_eq!(Lines.read_record(&mut reader).unwrap(), Some(b"b".to_vec()));
+        assert_eq!(Lines.read_record(&mut reader).unwrap(), None);
+    }
+
+    #[test]
+    fn truncated_records_are_errors() {
+        let error = FixedWidth { width: 4 }
+            .read_record(&mut &b"abc"[..])
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error = LengthPrefixed
+            .read_record(&mut &b"\x05\0\0\0abc"[..])
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let error = FixedWidth { width: 4 }
+            .write_record(&b"abc".to_vec(), &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,366 @@
+use core::{cmp::Ordering, error::Error, fmt};
+
+use super::Sorter;
+
+/// [`FloatSorter`]
+///
+/// [`Sorter`] requires `T: Ord`, which `f32` and `f64` do not implement because of NaN. This
+/// adapter lets any sorter order floating point slices by viewing them as [`FloatKey`]s, a
+/// zero-cost wrapper whose [`Ord`] implementation is chosen by two policies:
+///
+/// | Policy          | Variant                        | Behaviour                                              |
+/// |-----------------|--------------------------------|--------------------------------------------------------|
+/// | [`NanPolicy`]   | `Last` *(default)*             | NaNs compare greater than every number.                |
+/// |                 | `First`                        | NaNs compare less than every number.                   |
+/// |                 | `Error`                        | The slice is left untouched and a [`NanError`] is returned. |
+/// | [`ZeroPolicy`]  | `Equal` *(default)*            | `-0.0 == +0.0`, as with IEEE 754 comparisons.          |
+/// |                 | `NegativeFirst`                | `-0.0 < +0.0`, as with [`f64::total_cmp`].             |
+///
+/// NaNs always compare equal to each other and elements that compare equal are never reordered
+/// by the adapter itself, so a stable wrapped sorter keeps NaNs and signed zeros in their original
+/// relative order.
+///
+/// ```
+/// use maniac::{FloatSorter, InsertionSorter, NanPolicy};
+///
+/// let mut items = vec![2.5, f64::NAN, -1.0, 0.0];
+/// let sorter = FloatSorter::new(InsertionSorter::default()).with_nan_policy(NanPolicy::First);
+/// sorter.sort(&mut items).unwrap();
+///
+/// assert!(items[0].is_nan());
+/// assert_eq!(items[1..], [-1.0, 0.0, 2.5]);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FloatSorter<S> {
+    /// The sorter doing the actual work on the [`FloatKey`] view of the slice.
+    pub sorter: S,
+    /// Where NaNs end up, or whether they are rejected.
+    pub nan_policy: NanPolicy,
+    /// Whether `-0.0` and `+0.0` are distinguished.
+    pub zero_policy: ZeroPolicy,
+}
+
+/// NaN handling strategies for [`FloatSorter`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// NaNs are moved to the end of the slice.
+    #[default]
+    Last,
+    /// NaNs are moved to the beginning of the slice.
+    First,
+    /// Sorting a slice containing a NaN fails with a [`NanError`].
+    Error,
+}
+
+/// Signed zero handling strategies for [`FloatSorter`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ZeroPolicy {
+    /// `-0.0` and `+0.0` compare equal.
+    #[default]
+    Equal,
+    /// `-0.0` sorts before `+0.0`.
+    NegativeFirst,
+}
+
+/// Error returned by [`FloatSorter::sort`] when [`NanPolicy::Error`] is selected and the slice
+/// contains a NaN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NanError {
+    /// Index of the first NaN of the slice.
+    pub index: usize,
+}
+
+impl fmt::Display for NanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "found NaN at index {}", self.index)
+    }
+}
+
+impl Error for NanError {}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+/// Floating point types that can be sorted by a [`FloatSorter`], i.e. `f32` and `f64`.
+pub trait Float: Copy + PartialOrd + sealed::Sealed {
+    /// Returns `true` if `self` is NaN.
+    fn is_nan(self) -> bool;
+    /// IEEE 754 `totalOrder` predicate, see [`f64::total_cmp`].
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+impl Float for f32 {
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f32::total_cmp(self, other)
+    }
+}
+
+impl Float for f64 {
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        f64::total_cmp(self, other)
+    }
+}
+
+/// Totally ordered view of a float, as sorted by [`FloatSorter`].
+///
+/// `NAN_FIRST` selects [`NanPolicy::First`] over [`NanPolicy::Last`] and `SIGNED_ZERO` selects
+/// [`ZeroPolicy::NegativeFirst`] over [`ZeroPolicy::Equal`].
+#[derive(Debug, Clone, Copy)]
+#[repr(transparent)]
+pub struct FloatKey<F, const NAN_FIRST: bool, const SIGNED_ZERO: bool>(pub F);
+
+impl<F: Float, const NAN_FIRST: bool, const SIGNED_ZERO: bool> Ord
+    for FloatKey<F, NAN_FIRST, SIGNED_ZERO>
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) if NAN_FIRST => Ordering::Less,
+            (true, false) => Ordering::Greater,
+            (false, true) if NAN_FIRST => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) if SIGNED_ZERO => self.0.total_cmp(&other.0),
+            (false, false) => self
+                .0
+                .partial_cmp(&other.0)
+                .expect("non-NaN floats are always comparable"),
+        }
+    }
+}
+
+impl<F: Float, const NAN_FIRST: bool, const SIGNED_ZERO: bool> PartialOrd
+    for FloatKey<F, NAN_FIRST, SIGNED_ZERO>
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Float, const NAN_FIRST: bool, const SIGNED_ZERO: bool> PartialEq
+    for FloatKey<F, NAN_FIRST, SIGNED_ZERO>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F: Float, const NAN_FIRST: bool, const SIGNED_ZERO: bool> Eq
+    for FloatKey<F, NAN_FIRST, SIGNED_ZERO>
+{
+}
+
+/// Sorters able to sort every [`FloatKey`] ordering of `F`. It is implemented for all sorters that
+/// are generic over `T: Ord`, i.e. every sorter of this crate.
+pub trait FloatCapable<F: Float>:
+    Sorter<FloatKey<F, false, false>>
+    + Sorter<FloatKey<F, false, true>>
+    + Sorter<FloatKey<F, true, false>>
+    + Sorter<FloatKey<F, true, true>>
+{
+}
+
+impl<F, S> FloatCapable<F> for S
+where
+    F: Float,
+    S: Sorter<FloatKey<F, false, false>>
+        + Sorter<FloatKey<F, false, true>>
+        + Sorter<FloatKey<F, true, false>>
+        + Sorter<FloatKey<F, true, true>>,
+{
+}
+
+/// Views `slice` as a slice of [`FloatKey`]s and sorts it with `sorter`.
+fn sort_keys<F, S, const NAN_FIRST: bool, const SIGNED_ZERO: bool>(sorter: S, slice: &mut [F])
+where
+    F: Float,
+    S: Sorter<FloatKey<F, NAN_FIRST, SIGNED_ZERO>>,
+{
+    let len = slice.len();
+    // SAFETY: `FloatKey<F, _, _>` is `#[repr(transparent)]` over `F`, so a `[F]` and a
+    // `[FloatKey<F, _, _>]` of the same length have the same layout. The reborrow is exclusive and
+    // lives no longer than `slice`.
+    let keys = unsafe {
+        core::slice::from_raw_parts_mut(
+            slice
+                .as_mut_ptr()
+                .cast::<FloatKey<F, NAN_FIRST, SIGNED_ZERO>>(),
+            len,
+        )
+    };
+    sorter.sort(keys);
+}
+
+impl<S> FloatSorter<S> {
+    /// Wraps `sorter` with the default policies: NaNs last and signed zeros equal.
+    pub fn new(sorter: S) -> Self {
+        Self {
+            sorter,
+            nan_policy: NanPolicy::default(),
+            zero_policy: ZeroPolicy::default(),
+        }
+    }
+
+    /// Returns the same sorter with the given [`NanPolicy`].
+    #[must_use]
+    pub fn with_nan_policy(self, nan_policy: NanPolicy) -> Self {
+        Self { nan_policy, ..self }
+    }
+
+    /// Returns the same sorter with the given [`ZeroPolicy`].
+    #[must_use]
+    pub fn with_zero_policy(self, zero_policy: ZeroPolicy) -> Self {
+        Self {
+            zero_policy,
+            ..self
+        }
+    }
+
+    /// Sorts `slice` in ascending order according to the selected policies.
+    ///
+    /// # Errors
+    ///
+    /// With [`NanPolicy::Error`], returns a [`NanError`] pointing at the first NaN of the slice,
+    /// which is then left untouched.
+    pub fn sort<F>(self, slice: &mut [F]) -> Result<(), NanError>
+    where
+        F: Float,
+        S: FloatCapable<F>,
+    {
+        let nan_first = match self.nan_policy {
+            NanPolicy::First => true,
+            NanPolicy::Last => false,
+            NanPolicy::Error => {
+                if let Some(index) = slice.iter().position(|x| x.is_nan()) {
+                    return Err(NanError { index });
+                }
+                false
+            }
+        };
+
+        match (nan_first, self.zero_policy) {
+            (false, ZeroPolicy::Equal) => sort_keys::<F, S, false, false>(self.sorter, slice),
+            (false, ZeroPolicy::NegativeFirst) => {
+                sort_keys::<F, S, false, true>(self.sorter, slice);
+            }
+            (true, ZeroPolicy::Equal) => sort_keys::<F, S, true, false>(self.sorter, slice),
+            (true, ZeroPolicy::NegativeFirst) => {
+                sort_keys::<F, S, true, true>(self.sorter, slice);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::float_cmp)] // Sorting only moves values, exact comparisons are intended.
+mod tests {
+    use super::*;
+    use crate::{
+        BubbleSorter, CocktailShakerSorter, CombSorter, Descending, GnomeSorter, InsertionSorter,
+        OddEvenSorter,
+    };
+
+    fn assert_sorts<S: FloatCapable<f64> + Copy>(sorter: S) {
+        let data = [
+            3.5,
+            f64::NAN,
+            -0.0,
+            f64::NEG_INFINITY,
+            0.0,
+            -2.0,
+            f64::INFINITY,
+            1.0,
+        ];
+
+        let mut last = data;
+        FloatSorter::new(sorter).sort(&mut last).unwrap();
+        assert_eq!(
+            last[..7],
+            [f64::NEG_INFINITY, -2.0, -0.0, 0.0, 1.0, 3.5, f64::INFINITY]
+        );
+        assert!(last[7].is_nan());
+
+        let mut first = data;
+        FloatSorter::new(sorter)
+            .with_nan_policy(NanPolicy::First)
+            .sort(&mut first)
+            .unwrap();
+        assert!(first[0].is_nan());
+        assert_eq!(first[1..], last[..7]);
+    }
+
+    #[test]
+    fn sorts_with_every_sorter() {
+        assert_sorts(BubbleSorter::default());
+        assert_sorts(CocktailShakerSorter);
+        assert_sorts(CombSorter::default());
+        assert_sorts(GnomeSorter);
+        assert_sorts(InsertionSorter::default());
+        assert_sorts(OddEvenSorter::default());
+    }
+
+    #[test]
+    fn sorts_f32() {
+        let mut data = [1.0_f32, f32::NAN, -1.0];
+        FloatSorter::new(GnomeSorter).sort(&mut data).unwrap();
+        assert_eq!(data[..2], [-1.0, 1.0]);
+        assert!(data[2].is_nan());
+    }
+
+    #[test]
+    fn nan_error_leaves_slice_untouched() {
+        let mut data = [2.0, 1.0, f64::NAN, f64::NAN];
+        let result = FloatSorter::new(InsertionSorter::default())
+            .with_nan_policy(NanPolicy::Error)
+            .sort(&mut data);
+        assert_eq!(result, Err(NanError { index: 2 }));
+        assert_eq!(data[..2], [2.0, 1.0]);
+
+        let mut data = [2.0, 1.0];
+        let result = FloatSorter::new(InsertionSorter::default())
+            .with_nan_policy(NanPolicy::Error)
+            .sort(&mut data);
+        assert_eq!(result, Ok(()));
+        assert_eq!(data, [1.0, 2.0]);
+    }
+
+    #[test]
+    fn signed_zeros() {
+        let data = [0.0_f64, -0.0, 0.0, -0.0];
+
+        // Stable sorter with equal zeros: original order is kept.
+        let mut equal = data;
+        FloatSorter::new(BubbleSorter::default())
+            .sort(&mut equal)
+            .unwrap();
+        let signs: Vec<bool> = equal.iter().map(|x| x.is_sign_negative()).collect();
+        assert_eq!(signs, [false, true, false, true]);
+
+        let mut signed = data;
+        FloatSorter::new(BubbleSorter::default())
+            .with_zero_policy(ZeroPolicy::NegativeFirst)
+            .sort(&mut signed)
+            .unwrap();
+        let signs: Vec<bool> = signed.iter().map(|x| x.is_sign_negative()).collect();
+        assert_eq!(signs, [true, true, false, false]);
+    }
+
+    #[test]
+    fn descending() {
+        let mut data = [1.0, f64::NAN, 3.0, 2.0];
+        FloatSorter::new(InsertionSorter::default().descending())
+            .sort(&mut data)
+            .unwrap();
+        assert!(data[0].is_nan());
+        assert_eq!(data[1..], [3.0, 2.0, 1.0]);
+    }
+}
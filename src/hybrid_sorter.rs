@@ -0,0 +1,85 @@
+use super::{Sorter, StableSorter};
+
+/// [`HybridSorter`]
+///
+/// Picks one of two sorters by the length of the input: slices of at most `threshold` elements are
+/// sorted by the `small` sorter, longer ones by the `large` one.
+///
+/// The length is checked once, on the whole input: `large` then sorts its own subslices, so the
+/// threshold never reaches its recursion. [`QuickSorter`](crate::QuickSorter),
+/// [`MergeSorter`](crate::MergeSorter) and [`SampleSorter`](crate::SampleSorter) already finish
+/// their short subslices with an insertion sort, so pairing them with a quadratic `small` sorter
+/// only changes how short inputs are sorted.
+///
+/// ```
+/// use maniac::{CombSorter, HybridSorter, InsertionSorter, Sorter, Swap};
+///
+/// let sorter = HybridSorter {
+///     large: CombSorter::default(),
+///     small: InsertionSorter { method: Swap },
+///     threshold: 16,
+/// };
+///
+/// let mut items = vec![5, 3, 1, 4, 2];
+/// sorter.sort(&mut items);
+/// assert_eq!(items, vec![1, 2, 3, 4, 5]);
+/// ```
+///
+/// ### Complexity
+/// Those of `large`: `small` only ever sorts inputs of a bounded length.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HybridSorter<L, S> {
+    /// The sorter of the inputs longer than `threshold`.
+    pub large: L,
+    /// The sorter of the inputs of at most `threshold` elements.
+    pub small: S,
+    /// Longest input sorted by `small`.
+    pub threshold: usize,
+}
+
+impl<T, L, S> Sorter<T> for HybridSorter<L, S>
+where
+    T: Ord,
+    L: Sorter<T>,
+    S: Sorter<T>,
+{
+    fn sort(self, slice: &mut [T]) {
+        if slice.len() <= self.threshold {
+            self.small.sort(slice);
+        } else {
+            self.large.sort(slice);
+        }
+    }
+}
+
+impl<T, L, S> StableSorter<T> for HybridSorter<L, S>
+where
+    T: Ord,
+    L: StableSorter<T>,
+    S: StableSorter<T>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InsertionSorter, QuickSorter, Swap, sorter_tests};
+
+    // The tests sort slices on both sides of the threshold.
+    sorter_tests!(
+        hybrid,
+        HybridSorter {
+            large: QuickSorter::default(),
+            small: InsertionSorter { method: Swap },
+            threshold: 16,
+        }
+    );
+    sorter_tests!(
+        hybrid_stable,
+        HybridSorter {
+            large: InsertionSorter { method: Swap },
+            small: InsertionSorter::default(),
+            threshold: 8,
+        }
+    );
+}
@@ -0,0 +1,614 @@
+use super::{
+    BubbleSorter, CocktailShakerSorter, CombSorter, Finish, GnomeSorter, InsertionSorter, Metadata,
+    Method, Optimization, SorterMetadata, Swap, gnome_sorter,
+};
+
+/// [`IncrementalSort`]
+///
+/// A sort that can be paused and resumed: every call to [`step`](Self::step) performs at most a
+/// given number of comparisons on the slice, then returns, and the next call picks up where the
+/// previous one stopped. This spreads a sort over the frames of a game loop, sorting a bit of the
+/// list within the time budget of each frame.
+///
+/// It is built from a [`BubbleSorter`], a [`CocktailShakerSorter`], a [`CombSorter`], a
+/// [`GnomeSorter`] or an [`InsertionSorter`], and sorts like the sorter it was built from, except
+/// for the insertion sort, which always moves elements with [`Swap`]: the rotations
+/// of the other methods cannot be split into bounded steps.
+///
+/// ```
+/// use maniac::{GnomeSorter, IncrementalSort};
+///
+/// let mut items: Vec<u32> = (0..100).rev().collect();
+/// let mut sort = IncrementalSort::from(GnomeSorter);
+///
+/// let mut frames = 0;
+/// // At most 64 comparisons per frame.
+/// while !sort.step(&mut items, 64) {
+///     frames += 1;
+/// }
+/// assert!(items.iter().copied().eq(0..100));
+/// assert!(frames > 1);
+/// ```
+///
+/// The state only holds positions in the slice, so the same slice must be passed to every call.
+/// A slice of another length restarts the sort; if the elements change without the length
+/// changing, e.g. because the objects of the scene moved, call [`restart`](Self::restart).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IncrementalSort {
+    algorithm: Algorithm,
+    len: usize,
+    cursor: Cursor,
+}
+
+/// The sorter an [`IncrementalSort`] was built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Bubble(Optimization),
+    CocktailShaker,
+    Comb(CombSorter),
+    Gnome,
+    Insertion,
+}
+
+/// Where an [`IncrementalSort`] stands. Between two calls, every variant but `Start` and `Done`
+/// points to the next comparison to make, so that a step never ends on an empty pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cursor {
+    /// Nothing was done yet.
+    Start,
+    /// Compares `i - 1` and `i`, in the `passes`-th pass over `..n`.
+    Bubble {
+        n: usize,
+        i: usize,
+        last_swap: usize,
+        passes: usize,
+    },
+    /// Compares `i - 1` and `i` forward, `i - 2` and `i - 1` backward, in the range `left..right`.
+    CocktailShaker {
+        left: usize,
+        right: usize,
+        i: usize,
+        forward: bool,
+        last_swap: usize,
+        sorted: bool,
+    },
+    /// Compares `i` and `i + gap`.
+    Comb {
+        gap: usize,
+        i: usize,
+        sorted: bool,
+        final_passes: usize,
+    },
+    /// Compares `position - 1` and `position`.
+    Gnome { position: usize, swaps_left: u64 },
+    /// Compares `j - 1` and `j`, while inserting the element that was at `i`.
+    Insertion { i: usize, j: usize },
+    /// The slice is sorted.
+    Done,
+}
+
+impl IncrementalSort {
+    /// An incremental sort with the algorithm `algorithm`, not started yet.
+    const fn new(algorithm: Algorithm) -> Self {
+        Self {
+            algorithm,
+            len: 0,
+            cursor: Cursor::Start,
+        }
+    }
+
+    /// Performs at most `operations` comparisons, with the swaps they lead to, towards sorting
+    /// `slice`, and returns whether it is sorted.
+    ///
+    /// Once sorted, calls return `true` without comparing anything.
+    pub fn step<T: Ord>(&mut self, slice: &mut [T], operations: usize) -> bool {
+        if slice.len() != self.len {
+            self.len = slice.len();
+            self.cursor = Cursor::Start;
+        }
+        if self.cursor == Cursor::Start {
+            self.cursor = self.start();
+        }
+        for _ in 0..operations {
+            if self.cursor == Cursor::Done {
+                break;
+            }
+            self.cursor = self.compare(slice);
+        }
+        self.is_done()
+    }
+
+    /// Whether the last call to [`step`](Self::step) finished sorting the slice.
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.cursor == Cursor::Done
+    }
+
+    /// Forgets the progress made, so that the next call to [`step`](Self::step) sorts the slice
+    /// from the beginning.
+    pub fn restart(&mut self) {
+        self.cursor = Cursor::Start;
+    }
+
+    /// The cursor before the first comparison on a slice of `self.len` elements.
+    fn start(&self) -> Cursor {
+        let len = self.len;
+        match self.algorithm {
+            Algorithm::Bubble(_) if len > 1 => Cursor::Bubble {
+                n: len,
+                i: 1,
+                last_swap: 0,
+                passes: 1,
+            },
+            Algorithm::CocktailShaker if len > 1 => Cursor::CocktailShaker {
+                left: 1,
+                right: len,
+                i: 1,
+                forward: true,
+                last_swap: len,
+                sorted: true,
+            },
+            Algorithm::Comb(sorter) => next_comb_pass(sorter, len, len, false, 0),
+            // Every swap fixes one inversion, and there are at most n(n-1)/2 of them: only an
+            // inconsistent `Ord` can exhaust this budget, see `GnomeSorter`.
+            Algorithm::Gnome if len > 1 => Cursor::Gnome {
+                position: 1,
+                swaps_left: gnome_sorter::max_swaps(len),
+            },
+            Algorithm::Insertion if len > 1 => Cursor::Insertion { i: 1, j: 1 },
+            _ => Cursor::Done,
+        }
+    }
+
+    /// Makes the comparison the cursor points to, and returns the cursor of the next one.
+    fn compare<T: Ord>(&self, slice: &mut [T]) -> Cursor {
+        let len = self.len;
+        match self.cursor {
+            Cursor::Bubble {
+                n,
+                i,
+                mut last_swap,
+                passes,
+            } => {
+                if slice[i - 1] > slice[i] {
+                    slice.swap(i - 1, i);
+                    last_swap = i;
+                }
+                if i + 1 < n {
+                    return Cursor::Bubble {
+                        n,
+                        i: i + 1,
+                        last_swap,
+                        passes,
+                    };
+                }
+                let Algorithm::Bubble(optimization) = self.algorithm else {
+                    unreachable!("bubble cursor of another algorithm")
+                };
+                let n = match optimization {
+                    Optimization::NewLen => last_swap,
+                    Optimization::LenMinusOne => n - 1,
+                    Optimization::None => n,
+                };
+                // The same bound on the passes as `BubbleSorter`.
+                if last_swap == 0 || n <= 1 || passes >= len {
+                    return Cursor::Done;
+                }
+                Cursor::Bubble {
+                    n,
+                    i: 1,
+                    last_swap: 0,
+                    passes: passes + 1,
+                }
+            }
+            Cursor::CocktailShaker { .. } => self.compare_cocktail_shaker(slice),
+            Cursor::Comb {
+                gap,
+                i,
+                mut sorted,
+                final_passes,
+            } => {
+                if slice[i] > slice[i + gap] {
+                    slice.swap(i, i + gap);
+                    sorted = false;
+                }
+                if i + 1 + gap < len {
+                    return Cursor::Comb {
+                        gap,
+                        i: i + 1,
+                        sorted,
+                        final_passes,
+                    };
+                }
+                let Algorithm::Comb(sorter) = self.algorithm else {
+                    unreachable!("comb cursor of another algorithm")
+                };
+                next_comb_pass(sorter, len, gap, sorted, final_passes)
+            }
+            Cursor::Gnome {
+                mut position,
+                mut swaps_left,
+            } => {
+                if slice[position] >= slice[position - 1] {
+                    position += 1;
+                } else if swaps_left == 0 {
+                    return Cursor::Done;
+                } else {
+                    slice.swap(position, position - 1);
+                    swaps_left -= 1;
+                    // Position 0 has nothing on its left to compare with.
+                    position = position.saturating_sub(1).max(1);
+                }
+                if position < len {
+                    Cursor::Gnome {
+                        position,
+                        swaps_left,
+                    }
+                } else {
+                    Cursor::Done
+                }
+            }
+            Cursor::Insertion { i, j } => {
+                if slice[j - 1] > slice[j] {
+                    slice.swap(j - 1, j);
+                    if j > 1 {
+                        return Cursor::Insertion { i, j: j - 1 };
+                    }
+                }
+                if i + 1 < len {
+                    Cursor::Insertion { i: i + 1, j: i + 1 }
+                } else {
+                    Cursor::Done
+                }
+            }
+            Cursor::Start | Cursor::Done => self.cursor,
+        }
+    }
+
+    /// [`compare`](Self::compare) for the cocktail shaker sort, whose passes go both ways.
+    fn compare_cocktail_shaker<T: Ord>(&self, slice: &mut [T]) -> Cursor {
+        let Cursor::CocktailShaker {
+            mut left,
+            mut right,
+            mut i,
+            mut forward,
+            mut last_swap,
+            mut sorted,
+        } = self.cursor
+        else {
+            unreachable!("not a cocktail shaker cursor")
+        };
+
+        let compared = if forward { i } else { i - 1 };
+        if slice[compared - 1] > slice[compared] {
+            slice.swap(compared - 1, compared);
+            last_swap = if forward { compared } else { compared + 1 };
+            sorted = false;
+        }
+        if forward {
+            i += 1;
+        } else {
+            i -= 1;
+        }
+
+        // Skips the ends of the scans, and the scans left with nothing to compare.
+        loop {
+            if forward && i >= right {
+                right = last_swap;
+                forward = false;
+                i = right;
+                last_swap = left;
+            } else if !forward && i <= left {
+                left = last_swap;
+                if sorted {
+                    return Cursor::Done;
+                }
+                sorted = true;
+                forward = true;
+                i = left;
+                last_swap = right;
+            } else {
+                return Cursor::CocktailShaker {
+                    left,
+                    right,
+                    i,
+                    forward,
+                    last_swap,
+                    sorted,
+                };
+            }
+        }
+    }
+}
+
+/// The cursor of the first comparison of the comb sort pass following the one with `gap`, which
+/// left the slice `sorted` or not. Mirrors the loop of [`CombSorter`].
+fn next_comb_pass(
+    comb: CombSorter,
+    len: usize,
+    mut gap: usize,
+    mut sorted: bool,
+    mut final_passes: usize,
+) -> Cursor {
+    while !sorted {
+        gap = comb.next_gap(gap);
+        if gap == 1 {
+            if comb.finish() == Finish::Insertion {
+                return if len > 1 {
+                    Cursor::Insertion { i: 1, j: 1 }
+                } else {
+                    Cursor::Done
+                };
+            }
+            if final_passes == len {
+                break;
+            }
+            final_passes += 1;
+            sorted = true;
+        }
+        if gap < len {
+            return Cursor::Comb {
+                gap,
+                i: 0,
+                sorted,
+                final_passes,
+            };
+        }
+    }
+    Cursor::Done
+}
+
+impl From<BubbleSorter> for IncrementalSort {
+    fn from(sorter: BubbleSorter) -> Self {
+        Self::new(Algorithm::Bubble(sorter.optimization))
+    }
+}
+
+impl From<CocktailShakerSorter> for IncrementalSort {
+    fn from(_: CocktailShakerSorter) -> Self {
+        Self::new(Algorithm::CocktailShaker)
+    }
+}
+
+impl From<CombSorter> for IncrementalSort {
+    fn from(sorter: CombSorter) -> Self {
+        Self::new(Algorithm::Comb(sorter))
+    }
+}
+
+impl From<GnomeSorter> for IncrementalSort {
+    fn from(_: GnomeSorter) -> Self {
+        Self::new(Algorithm::Gnome)
+    }
+}
+
+impl<M: Method> From<InsertionSorter<M>> for IncrementalSort {
+    fn from(_: InsertionSorter<M>) -> Self {
+        Self::new(Algorithm::Insertion)
+    }
+}
+
+/// The metadata of the sorter it was built from, with [`Swap`] for the insertion sort.
+impl SorterMetadata for IncrementalSort {
+    fn metadata(&self) -> Metadata {
+        match self.algorithm {
+            Algorithm::Bubble(optimization) => BubbleSorter { optimization }.metadata(),
+            Algorithm::CocktailShaker => CocktailShakerSorter.metadata(),
+            Algorithm::Comb(sorter) => sorter.metadata(),
+            Algorithm::Gnome => GnomeSorter.metadata(),
+            Algorithm::Insertion => InsertionSorter { method: Swap }.metadata(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RotateUnstable, Sorter, dataset::Rng, sorter_tests};
+    use core::cmp::Ordering;
+    use std::cell::Cell;
+
+    /// Sorts with an [`IncrementalSort`] `operations` comparisons at a time, so that the sorter
+    /// tests resume sorts at every point.
+    #[derive(Debug, Clone, Copy)]
+    struct InSteps {
+        sort: IncrementalSort,
+        operations: usize,
+    }
+
+    impl<T: Ord> Sorter<T> for InSteps {
+        fn sort(mut self, slice: &mut [T]) {
+            while !self.sort.step(slice, self.operations) {}
+        }
+    }
+
+    impl SorterMetadata for InSteps {
+        fn metadata(&self) -> Metadata {
+            self.sort.metadata()
+        }
+    }
+
+    sorter_tests!(
+        bubble_in_steps,
+        InSteps {
+            sort: BubbleSorter::default().into(),
+            operations: 1,
+        }
+    );
+    sorter_tests!(
+        bubble_no_optim_in_steps,
+        InSteps {
+            sort: BubbleSorter {
+                optimization: Optimization::None
+            }
+            .into(),
+            operations: 3,
+        }
+    );
+    sorter_tests!(
+        cocktail_in_steps,
+        InSteps {
+            sort: CocktailShakerSorter.into(),
+            operations: 1,
+        }
+    );
+    sorter_tests!(
+        comb_in_steps,
+        InSteps {
+            sort: CombSorter::default().into(),
+            operations: 2,
+        }
+    );
+    sorter_tests!(
+        comb_final_insertion_in_steps,
+        InSteps {
+            sort: CombSorter::default().with_finish(Finish::Insertion).into(),
+            operations: 5,
+        }
+    );
+    sorter_tests!(
+        gnome_in_steps,
+        InSteps {
+            sort: GnomeSorter.into(),
+            operations: 1,
+        }
+    );
+    sorter_tests!(
+        insertion_in_steps,
+        InSteps {
+            sort: InsertionSorter::default().into(),
+            operations: 1,
+        }
+    );
+
+    /// Element counting the comparisons made on it.
+    #[derive(Debug, Clone, Copy)]
+    struct Counted<'a> {
+        value: u32,
+        comparisons: &'a Cell<usize>,
+    }
+
+    impl PartialEq for Counted<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for Counted<'_> {}
+
+    impl PartialOrd for Counted<'_> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Counted<'_> {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.comparisons.set(self.comparisons.get() + 1);
+            self.value.cmp(&other.value)
+        }
+    }
+
+    /// Checks that sorting in steps of `operations` comparisons makes at most that many per step,
+    /// and the same comparisons as `sorter` in total.
+    fn assert_same_comparisons<S>(sorter: S, operations: usize)
+    where
+        S: for<'a> Sorter<Counted<'a>> + Into<IncrementalSort> + Copy,
+    {
+        let mut rng = Rng::new(11);
+        let input: Vec<u32> = (0..300)
+            .map(|_| u32::try_from(rng.below(100)).unwrap())
+            .collect();
+        let comparisons = Cell::new(0);
+        let counted = |value| Counted {
+            value,
+            comparisons: &comparisons,
+        };
+
+        let mut expected: Vec<_> = input.iter().copied().map(counted).collect();
+        sorter.sort(&mut expected);
+        let expected_comparisons = comparisons.replace(0);
+
+        let mut items: Vec<_> = input.iter().copied().map(counted).collect();
+        let mut sort = sorter.into();
+        let mut total = 0;
+        while !sort.step(&mut items, operations) {
+            let step = comparisons.replace(0);
+            assert!(step <= operations, "{step} comparisons in one step");
+            total += step;
+        }
+        total += comparisons.get();
+        assert_eq!(total, expected_comparisons);
+        assert!(
+            items
+                .iter()
+                .map(|item| item.value)
+                .eq(expected.iter().map(|item| item.value))
+        );
+    }
+
+    #[test]
+    fn steps_split_the_comparisons_of_the_sorter() {
+        for operations in [1, 7, 100] {
+            assert_same_comparisons(BubbleSorter::default(), operations);
+            assert_same_comparisons(
+                BubbleSorter {
+                    optimization: Optimization::LenMinusOne,
+                },
+                operations,
+            );
+            assert_same_comparisons(CocktailShakerSorter, operations);
+            assert_same_comparisons(CombSorter::default(), operations);
+            assert_same_comparisons(CombSorter::default().with_rule_of_11(false), operations);
+            assert_same_comparisons(GnomeSorter, operations);
+            assert_same_comparisons(InsertionSorter { method: Swap }, operations);
+        }
+    }
+
+    #[test]
+    fn insertion_methods_all_swap() {
+        assert_eq!(
+            IncrementalSort::from(InsertionSorter {
+                method: RotateUnstable
+            }),
+            IncrementalSort::from(InsertionSorter { method: Swap })
+        );
+        assert!(
+            IncrementalSort::from(InsertionSorter {
+                method: RotateUnstable
+            })
+            .metadata()
+            .stable
+        );
+    }
+
+    #[test]
+    fn zero_operations_do_nothing() {
+        let mut items = [3, 1, 2];
+        let mut sort = IncrementalSort::from(GnomeSorter);
+        assert!(!sort.step(&mut items, 0));
+        assert_eq!(items, [3, 1, 2]);
+        assert!(IncrementalSort::from(GnomeSorter).step(&mut [1], 0));
+    }
+
+    #[test]
+    fn restarts() {
+        let mut sort = IncrementalSort::from(BubbleSorter::default());
+        let mut items = vec![3, 1, 2];
+        assert!(sort.step(&mut items, usize::MAX));
+        assert!(sort.is_done());
+
+        // The elements changed: restarting sorts them again.
+        items[0] = 4;
+        sort.restart();
+        assert!(!sort.is_done());
+        assert!(sort.step(&mut items, usize::MAX));
+        assert_eq!(items, [2, 3, 4]);
+
+        // A slice of another length restarts the sort by itself.
+        items.push(1);
+        assert!(!sort.step(&mut items, 1));
+        assert!(sort.step(&mut items, usize::MAX));
+        assert_eq!(items, [1, 2, 3, 4]);
+    }
+}
@@ -1,4 +1,4 @@
-use super::Sorter;
+use super::{Cancelled, ControlledSorter, SortControl, Sorter, StableSorter};
 
 /// [`InsertionSorter`]
 ///
@@ -8,112 +8,157 @@ use super::Sorter;
 /// `[..=i]` is guaranteed to be in order.
 ///
 /// This implementation lets you choose **how** the out-of-place element is moved into position via
-/// the [`Method`] field:
+/// the [`Method`] type parameter:
 ///
-/// | Method                     | Stable | Inner data move                | Remarks                                      |
-/// |----------------------------|:------:|--------------------------------|----------------------------------------------|
-/// | [`Method::RotateStable`]   | ✅     | `rotate_right(1)` after *upper-bound* search | Each element moved exactly once; fastest **and** stable. |
-/// | [`Method::RotateUnstable`] | ❌     | `rotate_right(1)` after *lower-bound* search | Slightly fewer comparisons, but equal keys can swap order. |
-/// | [`Method::Swap`]           | ✅     | Classic adjacent swaps          | Text-book variant; simpler but 2 × more element moves. |
+/// | Method              | Stable | Inner data move                | Remarks                                      |
+/// |---------------------|:------:|--------------------------------|----------------------------------------------|
+/// | [`RotateStable`]    | ✅     | `rotate_right(1)` after *upper-bound* search | Each element moved exactly once; fastest **and** stable. |
+/// | [`RotateUnstable`]  | ❌     | `rotate_right(1)` after *lower-bound* search | Slightly fewer comparisons, but equal keys can swap order. |
+/// | [`Swap`]            | ✅     | Classic adjacent swaps          | Text-book variant; simpler but 2 × more element moves. |
+///
+/// The method is part of the type so that only the stable methods implement [`StableSorter`]:
+///
+/// ```compile_fail
+/// use maniac::{InsertionSorter, RotateUnstable, StableSorter};
+///
+/// fn require_stable<S: StableSorter<i32>>(_: S) {}
+///
+/// require_stable(InsertionSorter {
+///     method: RotateUnstable,
+/// });
+/// ```
 ///
 /// ### Complexity
 /// | Best | Average | Worst | Space | In-place | Stable |
 /// | ---- | ------- | ----- | ----- | -------- | ------ |
-/// | *O(n)* (already sorted) | *O(n²)* | *O(n²)* | *O(1)* | Yes | see table above |
+/// | *O(n)* (already sorted, `Swap`) / *O(n log n)* (`Rotate*`) | *O(n²)* | *O(n²)* | *O(1)* | Yes | see table above |
 ///
-#[derive(Debug, Default, Clone, Copy)]
-pub struct InsertionSorter {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InsertionSorter<M: Method = RotateStable> {
     /// Strategy used to move the current element into its final position.
-    pub method: Method,
+    pub method: M,
 }
 
-/// Movement strategy for [`InsertionSorter`].
+impl Default for InsertionSorter {
+    fn default() -> Self {
+        Self {
+            method: RotateStable,
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::RotateStable {}
+    impl Sealed for super::RotateUnstable {}
+    impl Sealed for super::Swap {}
+}
+
+/// Movement strategy for [`InsertionSorter`], implemented by [`RotateStable`],
+/// [`RotateUnstable`] and [`Swap`].
 ///
 /// See the comparison table in [`InsertionSorter`] for details.
-#[derive(Debug, Default, Clone, Copy)]
-pub enum Method {
-    /// Bulk-move the block `[idx..=i]` one position to the right **after** the *upper-bound*,
-    /// preserving the order of elements that compare equal to the key (⚠️ *stable*).
-    #[default]
-    RotateStable,
-    /// Rotate after the *lower-bound*; the key lands **before** equal elements, so the
-    /// implementation is *not stable* but can be marginally faster.
-    RotateUnstable,
-    /// Swap neighbours until the key is in place – the traditional insertion-sort loop.
-    /// Still stable, yet it writes each moved element twice.
-    Swap,
+pub trait Method: Copy + sealed::Sealed {
+    /// Inserts the last element of `slice` into the rest of it, which is sorted.
+    fn insert<T: Ord>(slice: &mut [T]);
+
+    /// Sorts `slice` by inserting each element into the sorted prefix on its left.
+    fn insert_all<T: Ord>(slice: &mut [T]) {
+        for i in 1..slice.len() {
+            Self::insert(&mut slice[..=i]);
+        }
+    }
+}
+
+/// Bulk-move the block `[idx..=i]` one position to the right **after** the *upper-bound*,
+/// preserving the order of elements that compare equal to the key (⚠️ *stable*).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RotateStable;
+
+/// Rotate after the *lower-bound*; the key lands **before** equal elements, so the
+/// implementation is *not stable* but can be marginally faster.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RotateUnstable;
+
+/// Swap neighbours until the key is in place – the traditional insertion-sort loop.
+/// Still stable, yet it writes each moved element twice.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Swap;
+
+impl Method for RotateStable {
+    fn insert<T: Ord>(slice: &mut [T]) {
+        let Some((key, prefix)) = slice.split_last() else {
+            return;
+        };
+        let idx = prefix.partition_point(|x| x <= key);
+
+        if idx != prefix.len() {
+            slice[idx..].rotate_right(1);
+        }
+    }
 }
 
-impl<T: Ord> Sorter<T> for InsertionSorter {
+impl Method for RotateUnstable {
+    fn insert<T: Ord>(slice: &mut [T]) {
+        let Some((key, prefix)) = slice.split_last() else {
+            return;
+        };
+        let index = match prefix.binary_search(key) {
+            Ok(i) | Err(i) => i,
+        };
+        if index != prefix.len() {
+            slice[index..].rotate_right(1);
+        }
+    }
+}
+
+impl Method for Swap {
+    fn insert<T: Ord>(slice: &mut [T]) {
+        let mut j = slice.len().saturating_sub(1);
+        while j > 0 && slice[j - 1] > slice[j] {
+            slice.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+impl<T: Ord, M: Method> Sorter<T> for InsertionSorter<M> {
     fn sort(self, slice: &mut [T]) {
-        let slice_len = slice.len();
-        match self.method {
-            Method::RotateStable => {
-                for i in 1..slice.len() {
-                    let key_pos = i;
-                    let idx = slice[..i].partition_point(|x| x <= &slice[key_pos]);
-
-                    if idx != i {
-                        slice[idx..=i].rotate_right(1);
-                    }
-                }
-            }
-            Method::RotateUnstable => {
-                for i in 1..slice_len {
-                    let index = match slice[..i].binary_search(&slice[i]) {
-                        Ok(i) | Err(i) => i,
-                    };
-                    if index != i {
-                        slice[index..=i].rotate_right(1);
-                    }
-                }
-            }
-            Method::Swap => {
-                for i in 1..slice_len {
-                    let mut j = i;
-                    while j > 0 && slice[j - 1] > slice[j] {
-                        slice.swap(j - 1, j);
-                        j -= 1;
-                    }
-                }
-            }
+        M::insert_all(slice);
+    }
+}
+
+/// A pass inserts one element into the sorted prefix.
+impl<T: Ord, M: Method> ControlledSorter<T> for InsertionSorter<M> {
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled> {
+        control.checkpoint(0)?;
+        for i in 1..slice.len() {
+            M::insert(&mut slice[..=i]);
+            control.checkpoint(i)?;
         }
+        Ok(())
     }
 }
 
+impl<T: Ord> StableSorter<T> for InsertionSorter<RotateStable> {}
+impl<T: Ord> StableSorter<T> for InsertionSorter<Swap> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{sorter_common_tests, sorter_stability_tests};
+    use crate::sorter_tests;
 
-    sorter_common_tests!(
-        insertion_common_rotate_stable,
-        InsertionSorter {
-            method: Method::RotateStable
-        }
-    );
-    sorter_common_tests!(
-        insertion_common_rotate_unstable,
-        InsertionSorter {
-            method: Method::RotateUnstable
-        }
-    );
-    sorter_common_tests!(
-        insertion_common_swap,
-        InsertionSorter {
-            method: Method::Swap
-        }
-    );
-    sorter_stability_tests!(
-        insertion_stability_rotate_stable,
+    sorter_tests!(
+        insertion_rotate_stable,
         InsertionSorter {
-            method: Method::RotateStable
+            method: RotateStable
         }
     );
-    sorter_stability_tests!(
-        insertion_stability_swap,
+    sorter_tests!(
+        insertion_rotate_unstable,
         InsertionSorter {
-            method: Method::Swap
+            method: RotateUnstable
         }
     );
+    sorter_tests!(insertion_swap, InsertionSorter { method: Swap });
 }
@@ -0,0 +1,256 @@
+use alloc::vec::Vec;
+use core::{cmp::Ordering, fmt, iter::FusedIterator};
+
+/// [`KMerge`]
+///
+/// Lazily merges any number of sorted iterators into a single sorted iterator, see [`kmerge`],
+/// [`kmerge_by`] and [`kmerge_by_key`]. The sources are kept in a binary heap of their next
+/// items, so every item costs *O(log k)* comparisons for *k* sources.
+///
+/// The merge is stable: items comparing equal come out in the order of their sources, and in
+/// their order within a source. Building the iterator takes the first item of every source,
+/// every other item is only pulled when the previous item of its source has been yielded.
+///
+/// If a source is not sorted with respect to the comparator, the output is not sorted either,
+/// but it still holds every item of every source exactly once.
+///
+/// ```
+/// use maniac::kmerge;
+///
+/// let merged: Vec<i32> = kmerge([vec![1, 4, 7], vec![2, 5], vec![0, 3, 6, 8]]).collect();
+/// assert_eq!(merged, [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+/// ```
+pub struct KMerge<I: Iterator, F> {
+    /// Non-empty sources, as a binary min-heap of their heads.
+    heap: Vec<Source<I>>,
+    compare: F,
+}
+
+/// A source of a [`KMerge`] and its next item.
+struct Source<I: Iterator> {
+    head: I::Item,
+    /// Position of the source among the merged ones, breaking ties between equal heads.
+    index: usize,
+    iter: I,
+}
+
+/// Comparator of [`kmerge`], the natural order of the items.
+pub type Ascending<T> = fn(&T, &T) -> Ordering;
+
+/// Merges the sorted `iterables` in ascending order.
+pub fn kmerge<I>(iterables: impl IntoIterator<Item = I>) -> KMerge<I::IntoIter, Ascending<I::Item>>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    kmerge_by(iterables, Ord::cmp)
+}
+
+/// Merges the `iterables`, sorted with respect to `compare`, in the order of `compare`.
+///
+/// ```
+/// use maniac::kmerge_by;
+///
+/// let merged: Vec<i32> = kmerge_by([vec![7, 4, 1], vec![5, 2]], |a, b| b.cmp(a)).collect();
+/// assert_eq!(merged, [7, 5, 4, 2, 1]);
+/// ```
+pub fn kmerge_by<I, F>(iterables: impl IntoIterator<Item = I>, compare: F) -> KMerge<I::IntoIter, F>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    let heap = iterables
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, iterable)| {
+            let mut iter = iterable.into_iter();
+            Some(Source {
+                head: iter.next()?,
+                index,
+                iter,
+            })
+        })
+        .collect();
+    let mut merge = KMerge { heap, compare };
+    for position in (0..merge.heap.len() / 2).rev() {
+        merge.sift_down(position);
+    }
+    merge
+}
+
+/// Merges the `iterables`, sorted by the key `key` extracts, in ascending order of the keys.
+///
+/// ```
+/// use maniac::kmerge_by_key;
+///
+/// let merged: Vec<&str> =
+///     kmerge_by_key([vec!["b", "ccc"], vec!["aa", "dd"]], |word| word.len()).collect();
+/// assert_eq!(merged, ["b", "aa", "dd", "ccc"]);
+/// ```
+pub fn kmerge_by_key<I, K>(
+    iterables: impl IntoIterator<Item = I>,
+    mut key: impl FnMut(&I::Item) -> K,
+) -> KMerge<I::IntoIter, impl FnMut(&I::Item, &I::Item) -> Ordering>
+where
+    I: IntoIterator,
+    K: Ord,
+{
+    kmerge_by(iterables, move |a, b| key(a).cmp(&key(b)))
+}
+
+impl<I, F> KMerge<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    /// Whether the source at `a` must be merged before the one at `b`.
+    fn precedes(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (&self.heap[a], &self.heap[b]);
+        (self.compare)(&a.head, &b.head)
+            .then(a.index.cmp(&b.index))
+            .is_lt()
+    }
+
+    /// Moves the source at `position` down the heap until it precedes its children.
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let mut first = position;
+            for child in [2 * position + 1, 2 * position + 2] {
+                if child < self.heap.len() && self.precedes(child, first) {
+                    first = child;
+                }
+            }
+            if first == position {
+                return;
+            }
+            self.heap.swap(position, first);
+            position = first;
+        }
+    }
+}
+
+impl<I, F> Iterator for KMerge<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let first = self.heap.first_mut()?;
+        let item = match first.iter.next() {
+            Some(next) => core::mem::replace(&mut first.head, next),
+            None => self.heap.swap_remove(0).head,
+        };
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.heap
+            .iter()
+            .map(|source| {
+                let (low, high) = source.iter.size_hint();
+                (
+                    low.saturating_add(1),
+                    high.and_then(|high| high.checked_add(1)),
+                )
+            })
+            .fold((0, Some(0)), |(low, high), (source_low, source_high)| {
+                (
+                    low.saturating_add(source_low),
+                    high.zip(source_high).and_then(|(a, b)| a.checked_add(b)),
+                )
+            })
+    }
+}
+
+impl<I, F> FusedIterator for KMerge<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+}
+
+impl<I, F> fmt::Debug for KMerge<I, F>
+where
+    I: Iterator,
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KMerge")
+            .field(
+                "heads",
+                &self
+                    .heap
+                    .iter()
+                    .map(|source| &source.head)
+                    .collect::<Vec<_>>(),
+            )
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::Rng;
+
+    #[test]
+    fn merges_random_sorted_sources() {
+        let mut rng = Rng::new(3);
+        for sources_len in 0..20 {
+            let sources: Vec<Vec<usize>> = (0..sources_len)
+                .map(|_| {
+                    let len = rng.below(30);
+                    let mut source: Vec<usize> = (0..len).map(|_| rng.below(50)).collect();
+                    source.sort_unstable();
+                    source
+                })
+                .collect();
+            let mut expected = sources.concat();
+            expected.sort_unstable();
+
+            let merged = kmerge(sources.clone());
+            let total = expected.len();
+            assert_eq!(merged.size_hint(), (total, Some(total)));
+            assert_eq!(merged.collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn equal_items_keep_the_source_order() {
+        let sources = [
+            vec![(1, 'a'), (1, 'b'), (3, 'c')],
+            vec![(0, 'd'), (1, 'e'), (3, 'f')],
+            vec![],
+            vec![(1, 'g'), (2, 'h')],
+        ];
+        let merged: String = kmerge_by_key(sources, |&(key, _)| key)
+            .map(|(_, label)| label)
+            .collect();
+        assert_eq!(merged, "dabeghcf");
+    }
+
+    #[test]
+    fn pulls_items_lazily() {
+        let pulled = std::cell::Cell::new(0);
+        let source = |items: Vec<i32>| items.into_iter().inspect(|_| pulled.set(pulled.get() + 1));
+        let mut merged = kmerge([source(vec![1, 3, 5]), source(vec![2, 4, 6])]);
+        assert_eq!(pulled.get(), 2);
+        assert_eq!(merged.next(), Some(1));
+        assert_eq!(pulled.get(), 3);
+        assert_eq!(merged.by_ref().take(3).collect::<Vec<_>>(), [2, 3, 4]);
+        assert_eq!(pulled.get(), 6);
+        assert_eq!(merged.collect::<Vec<_>>(), [5, 6]);
+    }
+
+    #[test]
+    fn unsorted_sources_lose_no_item() {
+        let mut merged: Vec<i32> = kmerge([vec![3, 1, 2], vec![0, 5, 4]]).collect();
+        merged.sort_unstable();
+        assert_eq!(merged, [0, 1, 2, 3, 4, 5]);
+    }
+}
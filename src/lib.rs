@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(
     clippy::correctness,
     clippy::pedantic,
@@ -11,6 +12,22 @@
 //! # Maniac
 //! The maniac crate implements various sorting algorithms. Most implementations are based on the
 //! wikipedia page of each specific algorithm.
+//!
+//! ## Features
+//!
+//! The crate is `#![no_std]` by default, so that the in-place sorters can be used on embedded
+//! targets:
+//!
+//! - Without any feature, only the sorters that need neither a heap nor threads are available:
+//!   the bubble, cocktail shaker, comb, gnome, insertion, odd-even and quick sorters, and the
+//!   [`ReverseSorter`] and [`FloatSorter`] adapters.
+//! - `alloc` adds the sorters that need a buffer ([`MergeSorter`], [`SampleSorter`] and
+//!   [`StandardSorter`]), the [`registry`] with [`DynSorter`], and the [`dataset`],
+//!   [`kmerge`](mod@kmerge) and [`spec`] modules.
+//! - `std` implies `alloc` and adds the [`pool`] the parallel sorters run on,
+//!   [`bench`](mod@bench) and [`external`]. Without it, the parallel sorters sort on the current
+//!   thread.
+//! - `testkit` implies `std` and adds the [`testkit`] module.
 
 /// [`CombSorter`] is the implementation of the [comb sort algorithm](https://en.wikipedia.org/wiki/Comb_sort)
 pub mod comb_sorter;
@@ -27,15 +44,98 @@ pub use cocktail_shaker_sorter::*;
 /// [`GnomeSorter`] is the implementation of the [gnome sort](https://en.wikipedia.org/wiki/Gnome_sort)
 pub mod gnome_sorter;
 pub use gnome_sorter::*;
-/// [`OddEvenSorter`] is the implementation TEST MY LINK of the [odd-even sort](https://en.wikipedia.org/wiki/Odd%E2%80%93even_sort)
+/// [`OddEvenSorter`] is the implementation of the [odd-even sort](https://en.wikipedia.org/wiki/Odd%E2%80%93even_sort)
 pub mod odd_even_sorter;
 pub use odd_even_sorter::*;
+/// [`MergeSorter`] is the implementation of a parallel [merge sort](https://en.wikipedia.org/wiki/Merge_sort)
+#[cfg(feature = "alloc")]
+pub mod merge_sorter;
+#[cfg(feature = "alloc")]
+pub use merge_sorter::*;
+/// [`QuickSorter`] is the implementation of a parallel [quicksort](https://en.wikipedia.org/wiki/Quicksort)
+pub mod quick_sorter;
+pub use quick_sorter::*;
+/// [`Sequential`] and [`Parallel`] choose whether the [`QuickSorter`] and the [`MergeSorter`] split
+/// their work across threads
+pub mod parallelism;
+pub use parallelism::*;
+/// [`SampleSorter`] is the implementation of a parallel [sample sort](https://en.wikipedia.org/wiki/Samplesort)
+#[cfg(feature = "alloc")]
+pub mod sample_sorter;
+#[cfg(feature = "alloc")]
+pub use sample_sorter::*;
+/// [`ReverseSorter`] adapts any sorter to sort in descending order
+pub mod reverse_sorter;
+pub use reverse_sorter::*;
+/// [`FloatSorter`] adapts any sorter to sort `f32` and `f64` slices with a NaN policy
+pub mod float_sorter;
+pub use float_sorter::*;
+/// [`HybridSorter`] combines two sorters, picking one by slice length
+pub mod hybrid_sorter;
+pub use hybrid_sorter::*;
 
-mod standard_sorter;
+/// [`StandardSorter`] delegates to the standard library's sort, as a reference for comparisons
+#[cfg(feature = "alloc")]
+pub mod standard_sorter;
+#[cfg(feature = "alloc")]
+pub use standard_sorter::*;
+/// [`DynSorter`] is the object-safe version of [`Sorter`] and [`registry::all`] lists every
+/// sorting algorithm of the crate
+#[cfg(feature = "alloc")]
+pub mod registry;
+#[cfg(feature = "alloc")]
+pub use registry::{DynSorter, RegisteredSorter};
+/// [`SorterMetadata`] exposes the stability and complexity of every sorter
+pub mod metadata;
+pub use metadata::*;
+/// [`SortControl`] reports the progress of a [`ControlledSorter`] and lets a [`CancelToken`]
+/// stop it
+pub mod control;
+pub use control::*;
+/// [`IncrementalSort`] sorts a slice a bounded number of comparisons at a time, e.g. a bit every
+/// frame of a game loop
+pub mod incremental;
+pub use incremental::*;
+
+/// [`bench::Benchmarker`] times sorters on generated inputs and can track their allocations
+#[cfg(feature = "std")]
+pub mod bench;
+/// [`dataset::Distribution`] generates reproducible inputs of various shapes
+// Test builds always link `std`, and the tests generate their inputs with it.
+#[cfg(any(feature = "alloc", test))]
+pub mod dataset;
+/// [`KMerge`] lazily merges sorted iterators into one, e.g. the runs of an external sort
+#[cfg(feature = "alloc")]
+pub mod kmerge;
+#[cfg(feature = "alloc")]
+pub use kmerge::{KMerge, kmerge, kmerge_by, kmerge_by_key};
+/// [`external::ExternalSorter`] sorts files larger than memory through sorted runs on disk
+#[cfg(feature = "std")]
+pub mod external;
+/// [`pool::ThreadPool`] is the work-stealing thread pool the parallel sorters run on
+#[cfg(feature = "std")]
+pub mod pool;
+/// [`spec::Spec`] parses and prints textual sorter configurations such as `comb:shrink=1.25`
+#[cfg(feature = "alloc")]
+pub mod spec;
+/// Conformance test kit that any [`Sorter`] implementation can run, behind the `testkit` feature
+#[cfg(any(test, feature = "testkit"))]
+pub mod testkit;
+
+#[cfg(any(feature = "alloc", test))]
+extern crate alloc;
+
+mod parallel;
 mod test_macros;
 
 /// The [`Sorter`] trait is the only trait that is required to be implemented by any sorter.
 /// The only expected method is the sort method that is generic over a T where T is Ord.
+///
+/// # Panic safety
+///
+/// If a comparison panics midway, implementations must leave the slice as a permutation of the
+/// original: no element may be dropped, duplicated or leaked. Every sorter of this crate upholds
+/// this, which [`testkit::panic_safety`] checks by panicking at every possible comparison.
 pub trait Sorter<T>
 where
     T: Ord,
@@ -43,3 +143,23 @@ where
     /// This method is the implementation of the sorting algorithm
     fn sort(self, slice: &mut [T]);
 }
+
+/// The [`StableSorter`] marker trait is implemented only by sorters that keep elements comparing
+/// equal in their original relative order, in every configuration allowed by their type.
+///
+/// It lets APIs require stability at compile time:
+///
+/// ```
+/// use maniac::{InsertionSorter, StableSorter, Swap};
+///
+/// fn group<T: Ord, S: StableSorter<T>>(sorter: S, items: &mut [T]) {
+///     sorter.sort(items);
+/// }
+///
+/// group(InsertionSorter { method: Swap }, &mut [3, 1, 2]);
+/// ```
+pub trait StableSorter<T>: Sorter<T>
+where
+    T: Ord,
+{
+}
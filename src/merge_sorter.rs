@@ -0,0 +1,332 @@
+use alloc::vec::Vec;
+use core::{
+    mem::{ManuallyDrop, MaybeUninit},
+    ptr, slice,
+};
+
+use super::{
+    InsertionSorter, Parallel, Parallelism, Sequential, Sorter, StableSorter, Swap, parallel,
+};
+
+/// [`MergeSorter`]
+///
+/// The [*merge sort*](https://en.wikipedia.org/wiki/Merge_sort) splits the slice in two halves,
+/// sorts them recursively and merges the two sorted halves through a buffer as long as the slice.
+/// Short subslices are sorted by an insertion sort, and two halves already in order are not
+/// merged at all, so sorted inputs take a single comparison per subslice.
+///
+/// With [`Parallel`], both steps run in parallel on the current [pool](crate::pool):
+///
+/// 1.  The two halves of a subslice longer than the
+///     [`sequential_cutoff`](Parallel::sequential_cutoff) are sorted as two tasks.
+/// 2.  Their merge is split into independent merges by *co-ranking*: the middle element of the
+///     longer half is binary searched in the other half, which cuts both halves into a part
+///     merged before it and a part merged after it, as two tasks. The parts are split again
+///     until every thread has one.
+///
+/// Handing parts to other threads requires `T: Send`, which is why the choice is part of the
+/// type: the default [`Sequential`] sorter sorts any `T: Ord`.
+///
+/// If a comparison panics, the elements not merged yet are copied back to the slice, which is
+/// left a permutation of the original.
+///
+/// ```
+/// use maniac::{MergeSorter, Parallel, Sorter};
+///
+/// let mut items: Vec<u32> = (0..100_000).rev().collect();
+/// MergeSorter {
+///     parallelism: Parallel {
+///         threads: 4,
+///         sequential_cutoff: 1_000,
+///     },
+/// }
+/// .sort(&mut items);
+///
+/// assert!(items.is_sorted());
+/// ```
+///
+/// ### Complexity
+/// | Best | Average | Worst | Space | In-place | Stable |
+/// | ---- | ------- | ----- | ----- | -------- | ------ |
+/// | *O(n)* (already sorted) | *O(n log n)* | *O(n log n)* | *O(n)* | No | Yes |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeSorter<P: Parallelism = Sequential> {
+    /// Whether the halves are sorted, and merged, across the workers of the current pool.
+    pub parallelism: P,
+}
+
+impl Default for MergeSorter {
+    fn default() -> Self {
+        Self {
+            parallelism: Sequential,
+        }
+    }
+}
+
+/// Length under which subslices are sorted by an insertion sort.
+const INSERTION_LEN: usize = 24;
+
+impl<T: Ord> Sorter<T> for MergeSorter<Sequential> {
+    fn sort(self, slice: &mut [T]) {
+        with_buffer(slice, merge_sort);
+    }
+}
+
+impl<T: Ord + Send> Sorter<T> for MergeSorter<Parallel> {
+    fn sort(self, slice: &mut [T]) {
+        let threads = parallel::threads(self.parallelism.threads);
+        let cutoff = self.parallelism.sequential_cutoff;
+        with_buffer(slice, |slice, buffer| {
+            parallel_merge_sort(slice, buffer, threads, cutoff);
+        });
+    }
+}
+
+impl<T: Ord> StableSorter<T> for MergeSorter<Sequential> {}
+impl<T: Ord + Send> StableSorter<T> for MergeSorter<Parallel> {}
+
+/// Sorts `slice` with `sort`, which gets a buffer of the same length as scratch space, unless the
+/// slice is short enough for an insertion sort.
+fn with_buffer<T: Ord>(slice: &mut [T], sort: impl FnOnce(&mut [T], &mut [MaybeUninit<T>])) {
+    if slice.len() <= INSERTION_LEN {
+        InsertionSorter { method: Swap }.sort(slice);
+        return;
+    }
+    // The buffer never holds initialized elements as far as the `Vec` knows, so it drops none.
+    let mut buffer: Vec<T> = Vec::with_capacity(slice.len());
+    let buffer = &mut buffer.spare_capacity_mut()[..slice.len()];
+    sort(slice, buffer);
+}
+
+/// Sorts `slice` with `buffer`, of the same length, as scratch space, on the current thread.
+fn merge_sort<T: Ord>(slice: &mut [T], buffer: &mut [MaybeUninit<T>]) {
+    let len = slice.len();
+    if len <= INSERTION_LEN {
+        InsertionSorter { method: Swap }.sort(slice);
+        return;
+    }
+    let mid = len / 2;
+    let (left, right) = slice.split_at_mut(mid);
+    let (left_buffer, right_buffer) = buffer.split_at_mut(mid);
+    merge_sort(left, left_buffer);
+    merge_sort(right, right_buffer);
+    if let Some(merge) = Merge::halves(slice, buffer, mid) {
+        merge.run();
+    }
+}
+
+/// Same as [`merge_sort`] but over `threads` threads.
+fn parallel_merge_sort<T: Ord + Send>(
+    slice: &mut [T],
+    buffer: &mut [MaybeUninit<T>],
+    threads: usize,
+    cutoff: usize,
+) {
+    let len = slice.len();
+    if threads < 2 || len <= cutoff.max(INSERTION_LEN) {
+        merge_sort(slice, buffer);
+        return;
+    }
+    let mid = len / 2;
+    let (left, right) = slice.split_at_mut(mid);
+    let (left_buffer, right_buffer) = buffer.split_at_mut(mid);
+    let left_threads = threads / 2;
+    parallel::join(
+        || parallel_merge_sort(left, left_buffer, left_threads, cutoff),
+        || parallel_merge_sort(right, right_buffer, threads - left_threads, cutoff),
+    );
+    if let Some(merge) = Merge::halves(slice, buffer, mid) {
+        parallel_merge(merge, threads, cutoff);
+    }
+}
+
+/// Merges `merge`, splitting it over `threads` threads.
+fn parallel_merge<T: Ord + Send>(merge: Merge<T>, threads: usize, cutoff: usize) {
+    let (left_len, right_len) = (merge.left_len, merge.right_len);
+    if threads < 2 || left_len + right_len <= cutoff || left_len == 0 || right_len == 0 {
+        merge.run();
+        return;
+    }
+    let (first, second) = merge.split();
+    let first_threads = threads / 2;
+    parallel::join(
+        move || parallel_merge(first, first_threads, cutoff),
+        move || parallel_merge(second, threads - first_threads, cutoff),
+    );
+}
+
+/// A pending merge of two sorted runs of the buffer into `dest`, which has room for both.
+///
+/// It owns the elements of the runs: on drop, the elements not merged yet are copied to `dest`
+/// in their current order, so that a panicking comparison leaves the slice a permutation.
+///
+/// The runs are tracked by their lengths rather than by end pointers, which would not tell how
+/// many zero-sized elements are left.
+struct Merge<T> {
+    left: *const T,
+    left_len: usize,
+    right: *const T,
+    right_len: usize,
+    dest: *mut T,
+}
+
+// SAFETY: a `Merge` owns the elements of its runs and is the only writer of its destination,
+// sending it to another thread sends the elements.
+unsafe impl<T: Send> Send for Merge<T> {}
+
+impl<T: Ord> Merge<T> {
+    /// Moves the sorted runs `slice[..mid]` and `slice[mid..]` to `buffer`, of the same length,
+    /// and returns their merge back into `slice`, or `None` if the runs are already in order.
+    fn halves(slice: &mut [T], buffer: &mut [MaybeUninit<T>], mid: usize) -> Option<Self> {
+        if slice[mid - 1] <= slice[mid] {
+            return None;
+        }
+        let len = slice.len();
+        // SAFETY: the buffer has room for `len` elements and does not overlap the slice. Once
+        // copied, the elements are owned by the `Merge`, which writes each of them back to the
+        // slice exactly once, even if a comparison panics. Nothing can panic between the copy and
+        // its creation.
+        unsafe {
+            let runs = buffer.as_mut_ptr().cast::<T>();
+            ptr::copy_nonoverlapping(slice.as_ptr(), runs, len);
+            Some(Merge {
+                left: runs,
+                left_len: mid,
+                right: runs.add(mid),
+                right_len: len - mid,
+                dest: slice.as_mut_ptr(),
+            })
+        }
+    }
+
+    /// Merges the runs on the current thread.
+    fn run(mut self) {
+        while self.left_len > 0 && self.right_len > 0 {
+            // SAFETY: both runs are non-empty and `dest` has room for what is left of them. Equal
+            // elements are taken from the left run first, which keeps the merge stable.
+            unsafe {
+                let (run, run_len) = if *self.right < *self.left {
+                    (&mut self.right, &mut self.right_len)
+                } else {
+                    (&mut self.left, &mut self.left_len)
+                };
+                ptr::copy_nonoverlapping(*run, self.dest, 1);
+                *run = run.add(1);
+                *run_len -= 1;
+                self.dest = self.dest.add(1);
+            }
+        }
+        // Dropping copies the rest of the run that is not exhausted.
+    }
+
+    /// Splits the merge into two independent merges, whose destinations follow each other. The
+    /// middle element of the longer run is binary searched in the shorter one, and both runs are
+    /// cut there.
+    fn split(self) -> (Self, Self) {
+        let (left_len, right_len) = (self.left_len, self.right_len);
+        // SAFETY: the runs hold initialized elements, which nothing writes to until the merges
+        // are built.
+        let (left, right) = unsafe {
+            (
+                slice::from_raw_parts(self.left, left_len),
+                slice::from_raw_parts(self.right, right_len),
+            )
+        };
+        // Elements of the left run equal to the cut one go before it, those of the right run
+        // after it, for stability.
+        let (left_cut, right_cut) = if left_len >= right_len {
+            let cut = left_len / 2;
+            (cut, right.partition_point(|element| element < &left[cut]))
+        } else {
+            let cut = right_len / 2;
+            (left.partition_point(|element| element <= &right[cut]), cut)
+        };
+
+        let merge = ManuallyDrop::new(self);
+        // SAFETY: the cuts are within the runs, and the first merge fills exactly the
+        // `left_cut + right_cut` first places of the destination.
+        unsafe {
+            (
+                Merge {
+                    left: merge.left,
+                    left_len: left_cut,
+                    right: merge.right,
+                    right_len: right_cut,
+                    dest: merge.dest,
+                },
+                Merge {
+                    left: merge.left.add(left_cut),
+                    left_len: left_len - left_cut,
+                    right: merge.right.add(right_cut),
+                    right_len: right_len - right_cut,
+                    dest: merge.dest.add(left_cut + right_cut),
+                },
+            )
+        }
+    }
+}
+
+impl<T> Drop for Merge<T> {
+    fn drop(&mut self) {
+        // SAFETY: `dest` has room for exactly the elements left in the runs.
+        unsafe {
+            ptr::copy_nonoverlapping(self.left, self.dest, self.left_len);
+            ptr::copy_nonoverlapping(self.right, self.dest.add(self.left_len), self.right_len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sorter_tests, test_macros::Item};
+
+    sorter_tests!(merge, MergeSorter::default());
+    sorter_tests!(
+        merge_parallel,
+        MergeSorter {
+            parallelism: Parallel::default()
+        }
+    );
+    // Every subslice is split over the threads, to exercise the parallel paths on short inputs.
+    sorter_tests!(
+        merge_parallel_no_cutoff,
+        MergeSorter {
+            parallelism: Parallel {
+                threads: 4,
+                sequential_cutoff: 0,
+            }
+        }
+    );
+
+    #[test]
+    fn sequential_merge_sort_does_not_need_send() {
+        use std::rc::Rc;
+
+        let mut items: Vec<Rc<i32>> = (0..100).rev().map(Rc::new).collect();
+        MergeSorter::default().sort(&mut items);
+        assert!(items.is_sorted());
+    }
+
+    #[test]
+    fn parallel_merge_is_stable() {
+        let mut rng = crate::dataset::Rng::new(11);
+        let mut items: Vec<Item> = (0..20_000)
+            .map(|tag| Item {
+                key: i32::try_from(rng.below(100)).unwrap(),
+                tag,
+            })
+            .collect();
+        let mut expected = items.clone();
+        expected.sort();
+
+        MergeSorter {
+            parallelism: Parallel {
+                threads: 8,
+                sequential_cutoff: 256,
+            },
+        }
+        .sort(&mut items);
+        assert_eq!(items, expected);
+    }
+}
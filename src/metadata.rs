@@ -0,0 +1,353 @@
+use core::fmt;
+
+use super::{
+    BubbleSorter, CocktailShakerSorter, CombSorter, GnomeSorter, HybridSorter, InsertionSorter,
+    OddEvenSorter, Parallel, ParallelPhases, QuickSorter, ReverseSorter, RotateStable,
+    RotateUnstable, Sequential, SequentialPhases, Swap,
+};
+#[cfg(feature = "alloc")]
+use super::{MergeSorter, SampleSorter, StandardSorter};
+
+/// Asymptotic growth class of a time or space complexity, as a function of the slice length `n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum Complexity {
+    /// *O(1)*
+    Constant,
+    /// *O(log n)*
+    Logarithmic,
+    /// *O(n)*
+    Linear,
+    /// *O(n log n)*
+    Linearithmic,
+    /// *O(n²)*
+    Quadratic,
+}
+
+impl fmt::Display for Complexity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Constant => "O(1)",
+            Self::Logarithmic => "O(log n)",
+            Self::Linear => "O(n)",
+            Self::Linearithmic => "O(n log n)",
+            Self::Quadratic => "O(n²)",
+        })
+    }
+}
+
+/// Machine-readable version of the complexity table found in the documentation of every sorter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Metadata {
+    /// Elements that compare equal keep their relative order.
+    pub stable: bool,
+    /// The sort needs no buffer proportional to the input.
+    pub in_place: bool,
+    /// Time complexity on the most favourable input (usually an already sorted slice).
+    pub best: Complexity,
+    /// Time complexity on a random input.
+    pub average: Complexity,
+    /// Time complexity on the least favourable input.
+    pub worst: Complexity,
+    /// Extra memory needed on top of the slice.
+    pub space: Complexity,
+    /// The sort runs faster on inputs that are already partially sorted.
+    pub adaptive: bool,
+    /// The sort spreads its work over several threads.
+    pub parallel: bool,
+}
+
+/// [`SorterMetadata`]
+///
+/// Exposes the [`Metadata`] of a sorter. Metadata can depend on the configuration, e.g.
+/// [`InsertionSorter`] is unstable with [`RotateUnstable`] only.
+///
+/// ```
+/// use maniac::{Complexity, InsertionSorter, RotateUnstable, SorterMetadata};
+///
+/// let sorter = InsertionSorter {
+///     method: RotateUnstable,
+/// };
+///
+/// assert!(!sorter.metadata().stable);
+/// assert_eq!(sorter.metadata().worst, Complexity::Quadratic);
+/// ```
+pub trait SorterMetadata {
+    /// Returns the metadata of this sorter in its current configuration.
+    fn metadata(&self) -> Metadata;
+}
+
+/// Whether the parallel sorters can run on several threads, which needs the `std` feature.
+const PARALLEL: bool = cfg!(feature = "std");
+
+/// Metadata shared by the simple exchange sorts: bubble, cocktail shaker, gnome and odd-even.
+const EXCHANGE_SORT: Metadata = Metadata {
+    stable: true,
+    in_place: true,
+    best: Complexity::Linear,
+    average: Complexity::Quadratic,
+    worst: Complexity::Quadratic,
+    space: Complexity::Constant,
+    adaptive: true,
+    parallel: false,
+};
+
+/// Metadata of [`MergeSorter`] on the current thread.
+#[cfg(feature = "alloc")]
+const MERGE_SORT: Metadata = Metadata {
+    stable: true,
+    in_place: false,
+    best: Complexity::Linear,
+    average: Complexity::Linearithmic,
+    worst: Complexity::Linearithmic,
+    space: Complexity::Linear,
+    adaptive: true,
+    parallel: false,
+};
+
+/// Metadata of [`QuickSorter`] on the current thread.
+const QUICK_SORT: Metadata = Metadata {
+    stable: false,
+    in_place: true,
+    best: Complexity::Linearithmic,
+    average: Complexity::Linearithmic,
+    worst: Complexity::Quadratic,
+    space: Complexity::Logarithmic,
+    adaptive: false,
+    parallel: false,
+};
+
+#[cfg(feature = "alloc")]
+impl SorterMetadata for StandardSorter {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            stable: true,
+            in_place: false,
+            best: Complexity::Linear,
+            average: Complexity::Linearithmic,
+            worst: Complexity::Linearithmic,
+            space: Complexity::Linear,
+            adaptive: true,
+            parallel: false,
+        }
+    }
+}
+
+impl SorterMetadata for BubbleSorter {
+    fn metadata(&self) -> Metadata {
+        EXCHANGE_SORT
+    }
+}
+
+impl SorterMetadata for CocktailShakerSorter {
+    fn metadata(&self) -> Metadata {
+        EXCHANGE_SORT
+    }
+}
+
+impl SorterMetadata for CombSorter {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            stable: false,
+            in_place: true,
+            best: Complexity::Linearithmic,
+            average: Complexity::Quadratic,
+            worst: Complexity::Quadratic,
+            space: Complexity::Constant,
+            adaptive: false,
+            parallel: false,
+        }
+    }
+}
+
+impl SorterMetadata for GnomeSorter {
+    fn metadata(&self) -> Metadata {
+        EXCHANGE_SORT
+    }
+}
+
+// The binary search of the rotating methods costs log n comparisons per element even on sorted
+// inputs.
+impl SorterMetadata for InsertionSorter<RotateStable> {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            best: Complexity::Linearithmic,
+            ..EXCHANGE_SORT
+        }
+    }
+}
+
+impl SorterMetadata for InsertionSorter<RotateUnstable> {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            stable: false,
+            best: Complexity::Linearithmic,
+            ..EXCHANGE_SORT
+        }
+    }
+}
+
+impl SorterMetadata for InsertionSorter<Swap> {
+    fn metadata(&self) -> Metadata {
+        EXCHANGE_SORT
+    }
+}
+
+impl SorterMetadata for OddEvenSorter<SequentialPhases> {
+    fn metadata(&self) -> Metadata {
+        EXCHANGE_SORT
+    }
+}
+
+impl SorterMetadata for OddEvenSorter<ParallelPhases> {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            parallel: PARALLEL,
+            ..EXCHANGE_SORT
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl SorterMetadata for MergeSorter<Sequential> {
+    fn metadata(&self) -> Metadata {
+        MERGE_SORT
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl SorterMetadata for MergeSorter<Parallel> {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            parallel: PARALLEL && self.parallelism.threads != 1,
+            ..MERGE_SORT
+        }
+    }
+}
+
+impl SorterMetadata for QuickSorter<Sequential> {
+    fn metadata(&self) -> Metadata {
+        QUICK_SORT
+    }
+}
+
+impl SorterMetadata for QuickSorter<Parallel> {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            parallel: PARALLEL && self.parallelism.threads != 1,
+            ..QUICK_SORT
+        }
+    }
+}
+
+// Classifying against a fixed number of splitters is linear, the buckets cost what the inner
+// sorter costs.
+#[cfg(feature = "alloc")]
+impl<S: SorterMetadata> SorterMetadata for SampleSorter<S> {
+    fn metadata(&self) -> Metadata {
+        let inner = self.sorter.metadata();
+        Metadata {
+            stable: false,
+            in_place: false,
+            best: inner.best.max(Complexity::Linear),
+            average: inner.average.max(Complexity::Linear),
+            worst: inner.worst.max(Complexity::Linear),
+            space: Complexity::Linear,
+            adaptive: false,
+            parallel: PARALLEL && self.threads != 1,
+        }
+    }
+}
+
+impl<S: SorterMetadata> SorterMetadata for ReverseSorter<S> {
+    fn metadata(&self) -> Metadata {
+        self.sorter.metadata()
+    }
+}
+
+// The small sorter only sorts slices of a bounded length, which costs constant time and space.
+impl<L: SorterMetadata, S: SorterMetadata> SorterMetadata for HybridSorter<L, S> {
+    fn metadata(&self) -> Metadata {
+        let (large, small) = (self.large.metadata(), self.small.metadata());
+        Metadata {
+            stable: large.stable && small.stable,
+            in_place: large.in_place && small.in_place,
+            parallel: large.parallel || small.parallel,
+            ..large
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::StableSorter;
+
+    #[test]
+    fn complexity_display() {
+        assert_eq!(Complexity::Linearithmic.to_string(), "O(n log n)");
+        assert_eq!(Complexity::Quadratic.to_string(), "O(n²)");
+    }
+
+    #[test]
+    fn insertion_stability_depends_on_method() {
+        assert!(
+            InsertionSorter {
+                method: RotateStable
+            }
+            .metadata()
+            .stable
+        );
+        assert!(
+            !InsertionSorter {
+                method: RotateUnstable
+            }
+            .metadata()
+            .stable
+        );
+        assert!(InsertionSorter { method: Swap }.metadata().stable);
+    }
+
+    #[test]
+    fn stable_sorters_declare_stable_metadata() {
+        fn declared_stable<S: StableSorter<i32> + SorterMetadata>(sorter: &S) -> bool {
+            sorter.metadata().stable
+        }
+
+        assert!(declared_stable(&BubbleSorter::default()));
+        assert!(declared_stable(&CocktailShakerSorter));
+        assert!(declared_stable(&GnomeSorter));
+        assert!(declared_stable(&InsertionSorter {
+            method: RotateStable
+        }));
+        assert!(declared_stable(&InsertionSorter { method: Swap }));
+        assert!(declared_stable(&OddEvenSorter::default()));
+        assert!(declared_stable(&ReverseSorter::new(GnomeSorter)));
+        #[cfg(feature = "alloc")]
+        {
+            assert!(declared_stable(&StandardSorter));
+            assert!(declared_stable(&MergeSorter::default()));
+            assert!(declared_stable(&MergeSorter {
+                parallelism: Parallel::default()
+            }));
+            assert!(declared_stable(&HybridSorter {
+                large: MergeSorter::default(),
+                small: InsertionSorter { method: Swap },
+                threshold: 32,
+            }));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn registry_exposes_metadata() {
+        for entry in crate::registry::all::<i32>() {
+            assert_eq!(
+                entry.metadata.parallel,
+                PARALLEL && entry.variant == "parallel",
+                "{entry:?}"
+            );
+        }
+    }
+}
@@ -1,84 +1,205 @@
-// use std::fmt::Display;
+use core::sync::atomic::{AtomicBool, Ordering};
 
-use super::Sorter;
+use super::{Cancelled, ControlledSorter, SortControl, Sorter, StableSorter, parallel};
 
-/// Doc
-#[derive(Debug, Default, Clone, Copy)]
-pub struct OddEvenSorter {
-    parallel: bool,
+/// [`OddEvenSorter`]
+///
+/// The [*odd-even sort*](https://en.wikipedia.org/wiki/Odd%E2%80%93even_sort) (also called
+/// *odd-even transposition sort* or *brick sort*) is a bubble sort variant designed for parallel
+/// processors. Each round is made of two phases:
+///
+/// 1.  An **even phase** compares the pairs `[0 1] [2 3] [4 5] …` and swaps them if needed.
+/// 2.  An **odd phase** does the same with the pairs `[1 2] [3 4] [5 6] …`.
+///
+/// The pairs of a phase never overlap, so they can all be compared at the same time. With
+/// [`ParallelPhases`], every phase is split into one chunk of pairs per worker of the current
+/// [pool](crate::pool) and the chunks are processed on the workers. Each worker gets at least
+/// [`MIN_PAIRS_PER_THREAD`] pairs, so small slices are sorted on the current thread. Handing the
+/// pairs to other threads requires `T: Send`, which is why the choice is part of the type: the
+/// default [`SequentialPhases`] sort any `T: Ord`.
+///
+/// Only adjacent elements are ever swapped, so the algorithm is **stable**.
+///
+/// ```
+/// use maniac::{OddEvenSorter, ParallelPhases, Sorter};
+///
+/// let mut items = vec![5, 3, 1, 4, 2];
+/// OddEvenSorter {
+///     phases: ParallelPhases,
+/// }
+/// .sort(&mut items);
+///
+/// assert_eq!(items, vec![1, 2, 3, 4, 5]);
+/// ```
+///
+/// ### Complexity
+/// | Best                  | Average | Worst | Space | In-place | Stable |
+/// |-----------------------|---------|-------|-------|----------|--------|
+/// | *O(n)* (already sorted) | *O(n²)* | *O(n²)* | *O(1)* | Yes | Yes |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OddEvenSorter<P: Phases = SequentialPhases> {
+    /// Whether each phase is split across the workers of the current pool.
+    pub phases: P,
 }
 
-impl<T: Ord> Sorter<T> for OddEvenSorter {
+impl Default for OddEvenSorter {
+    fn default() -> Self {
+        Self {
+            phases: SequentialPhases,
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::SequentialPhases {}
+    impl Sealed for super::ParallelPhases {}
+}
+
+/// Where an [`OddEvenSorter`] compares the pairs of a phase, implemented by [`SequentialPhases`]
+/// and [`ParallelPhases`].
+pub trait Phases: Copy + sealed::Sealed {}
+
+/// Compares every pair on the current thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SequentialPhases;
+
+/// Splits every phase across the workers of the current pool.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelPhases;
+
+impl Phases for SequentialPhases {}
+impl Phases for ParallelPhases {}
+
+/// Minimum number of pairs handed to each thread by a parallel [`OddEvenSorter`], below which
+/// handing them to another thread costs more than it saves.
+pub const MIN_PAIRS_PER_THREAD: usize = 1024;
+
+/// Compares and swaps every `[2k 2k+1]` pair of `slice`, returns `true` if nothing was swapped.
+fn phase<T: Ord>(slice: &mut [T]) -> bool {
+    let mut sorted = true;
+    for pair in slice.chunks_exact_mut(2) {
+        if pair[0] > pair[1] {
+            pair.swap(0, 1);
+            sorted = false;
+        }
+    }
+    sorted
+}
+
+/// Same as [`phase`] but the pairs are distributed over `threads` workers of the current pool.
+fn parallel_phase<T: Ord + Send>(slice: &mut [T], threads: usize) -> bool {
+    // Chunks must have an even length so that no pair straddles two chunks.
+    let chunk_len = slice.len().div_ceil(threads).next_multiple_of(2).max(2);
+    let sorted = AtomicBool::new(true);
+    parallel::for_each(slice.chunks_mut(chunk_len), threads, |chunk| {
+        if !phase(chunk) {
+            sorted.store(false, Ordering::Relaxed);
+        }
+    });
+    sorted.into_inner()
+}
+
+/// Runs rounds of an even and an odd phase until a round swaps nothing, comparing the pairs of
+/// every phase with `run_phase`.
+fn sort_rounds<T: Ord>(
+    slice: &mut [T],
+    control: &SortControl<'_>,
+    mut run_phase: impl FnMut(&mut [T]) -> bool,
+) -> Result<(), Cancelled> {
+    control.checkpoint(0)?;
+    let slice_len = slice.len();
+
+    // `slice_len` phases sort any input, so `slice_len` rounds of two phases are plenty: the
+    // bound only matters for an inconsistent `Ord`.
+    let mut sorted = false;
+    let mut rounds = 0;
+    while !sorted && rounds < slice_len {
+        rounds += 1;
+        sorted = true;
+        for offset in [0, 1] {
+            sorted &= run_phase(&mut slice[offset..]);
+        }
+        control.checkpoint(rounds)?;
+    }
+    Ok(())
+}
+
+impl<T: Ord> Sorter<T> for OddEvenSorter<SequentialPhases> {
     fn sort(self, slice: &mut [T]) {
-        if self.parallel {
-            // get the number of CPU
-            // divide the lengh of the array by 2
-            // divide this number by the number of CPU
-            // if we have 100 numbers and 3 CPU:
-            // on first pass:
-            // CPU 0 will have pairs 0 3 6 9... => [0 1] [6 7] [12 13] [18 19]... => 1 7 13 19 etc.
-            // CPU 1 will have pairs 1 4 7 10... => [2 3] [8 9] [14 15] [20 21]... => 3 9 15 21 etc.
-            // CPU 2 will have pairs 2 5 8 11... => [4 5] [10 11] [16 17] [22 23]... => 5 11 17 23 etc.
-            let lenght = 100;
-            let mut cpus = vec![Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new()];
-
-            let mut i = 1;
-
-            while i < lenght {
-                for cpu in 0..cpus.len() {
-                    if !(i < lenght) {
-                        break;
-                    }
-                    cpus[cpu].push(i);
-                    i += 2;
-                }
-            }
-            println!("{cpus:#?}");
-        } else {
-            let slice_len = slice.len();
-            /*
-            Create pairs of odd-even index:
-            [1-2] [3-4] [5-6] ...
-            swap these if necessary
-            do the same with even-odd pairs => 1 [2-3] [4-5] 6
-            if among these 2 passes there were 0 swap then it's sorted
-            */
-            let mut sorted = false;
-            while !sorted {
-                sorted = true;
-
-                for i in (1..slice_len).filter(|val| val % 2 != 0) {
-                    if slice[i - 1] > slice[i] {
-                        slice.swap(i - 1, i);
-                        sorted = false
-                    }
-                }
-
-                for i in (1..slice_len).filter(|val| val % 2 == 0) {
-                    if slice[i - 1] > slice[i] {
-                        slice.swap(i - 1, i);
-                        sorted = false
-                    }
-                }
+        // Nothing cancels a default control.
+        let _ = self.sort_with(slice, &SortControl::new());
+    }
+}
+
+impl<T: Ord + Send> Sorter<T> for OddEvenSorter<ParallelPhases> {
+    fn sort(self, slice: &mut [T]) {
+        // Nothing cancels a default control.
+        let _ = self.sort_with(slice, &SortControl::new());
+    }
+}
+
+/// A pass is a round of an even and an odd phase.
+impl<T: Ord> ControlledSorter<T> for OddEvenSorter<SequentialPhases> {
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled> {
+        sort_rounds(slice, control, phase)
+    }
+}
+
+/// A pass is a round of an even and an odd phase.
+impl<T: Ord + Send> ControlledSorter<T> for OddEvenSorter<ParallelPhases> {
+    fn sort_with(self, slice: &mut [T], control: &SortControl<'_>) -> Result<(), Cancelled> {
+        let threads = parallel::threads(0).min(slice.len() / 2 / MIN_PAIRS_PER_THREAD);
+        sort_rounds(slice, control, |pairs| {
+            if threads > 1 {
+                parallel_phase(pairs, threads)
+            } else {
+                phase(pairs)
             }
-        }
+        })
     }
 }
 
+impl<T: Ord> StableSorter<T> for OddEvenSorter<SequentialPhases> {}
+impl<T: Ord + Send> StableSorter<T> for OddEvenSorter<ParallelPhases> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    // use crate::{sorter_common_tests, sorter_stability_tests};
+    use crate::sorter_tests;
+
+    sorter_tests!(odd_even, OddEvenSorter::default());
+    sorter_tests!(
+        odd_even_parallel,
+        OddEvenSorter {
+            phases: ParallelPhases
+        }
+    );
 
     #[test]
-    fn debug() {
-        let mut slice = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17];
-        // println!("{slice:?}");
-        let sorter = OddEvenSorter { parallel: true };
-        sorter.sort(&mut slice);
-        assert!(false);
+    fn sequential_phases_do_not_need_send() {
+        use std::rc::Rc;
+
+        let mut items: Vec<Rc<i32>> = [3, 1, 2].into_iter().map(Rc::new).collect();
+        OddEvenSorter::default().sort(&mut items);
+        assert_eq!(items, [1, 2, 3].map(Rc::new));
     }
 
-    // sorter_common_tests!(odd_even_common, OddEvenSorter { parallel: false });
-    // sorter_stability_tests!(odd_even_stability, OddEvenSorter { parallel: false });
+    #[test]
+    fn parallel_phase_matches_sequential_phase() {
+        let mut rng = crate::dataset::Rng::new(5);
+        for len in [0, 1, 2, 3, 10, 33, 100] {
+            for offset in [0, 1] {
+                let data: Vec<usize> = (0..len).map(|_| rng.below(16)).collect();
+                let mut sequential = data.clone();
+                let mut parallel = data;
+                let offset = offset.min(len);
+
+                let sequential_sorted = phase(&mut sequential[offset..]);
+                let parallel_sorted = parallel_phase(&mut parallel[offset..], 4);
+                assert_eq!(sequential, parallel);
+                assert_eq!(sequential_sorted, parallel_sorted);
+            }
+        }
+    }
 }
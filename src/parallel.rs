@@ -0,0 +1,114 @@
+//! Fork-join helpers of the parallel sorters, on the [pool](crate::pool) with the `std` feature
+//! and on the current thread without it.
+
+#[cfg(feature = "std")]
+use super::pool;
+
+/// Number of threads used by a sorter configured with `threads`: `threads` itself, or one per
+/// worker of the current [pool](pool::ThreadPool) if it is 0.
+#[cfg(feature = "std")]
+pub(crate) fn threads(threads: usize) -> usize {
+    if threads == 0 {
+        pool::current_workers()
+    } else {
+        threads
+    }
+}
+
+/// Without threads, every sorter runs on the current thread only.
+#[cfg(not(feature = "std"))]
+pub(crate) fn threads(_threads: usize) -> usize {
+    1
+}
+
+/// Runs `a` and `b` on the current pool, potentially in parallel, and returns both results once
+/// both are done. A panic of either closure is propagated with its original payload, after the
+/// other one has finished.
+#[cfg(feature = "std")]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    pool::join(a, b)
+}
+
+/// Runs `a`, then `b`.
+#[cfg(not(feature = "std"))]
+pub(crate) fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    (a(), b())
+}
+
+/// Runs `f` on every task of `tasks`, split in halves over the current pool until every task is
+/// on its own, or on the current thread only if `threads` is 1. The tasks are started in order,
+/// so the longest ones should come first. A panic of `f` is propagated with its original
+/// payload, after the other tasks have run.
+#[cfg(feature = "std")]
+pub(crate) fn for_each<I, F>(tasks: impl IntoIterator<Item = I>, threads: usize, f: F)
+where
+    I: Send,
+    F: Fn(I) + Sync,
+{
+    let tasks: Vec<I> = tasks.into_iter().collect();
+    if threads <= 1 {
+        tasks.into_iter().for_each(f);
+    } else {
+        split(tasks, &f);
+    }
+}
+
+/// Runs `f` on every task of `tasks`, in order.
+#[cfg(not(feature = "std"))]
+pub(crate) fn for_each<I, F>(tasks: impl IntoIterator<Item = I>, _threads: usize, f: F)
+where
+    I: Send,
+    F: Fn(I) + Sync,
+{
+    tasks.into_iter().for_each(f);
+}
+
+#[cfg(feature = "std")]
+fn split<I: Send, F: Fn(I) + Sync>(mut tasks: Vec<I>, f: &F) {
+    if tasks.len() <= 1 {
+        tasks.into_iter().for_each(f);
+        return;
+    }
+    let second = tasks.split_off(tasks.len() / 2);
+    join(|| split(tasks, f), || split(second, f));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        panic,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    #[test]
+    fn for_each_runs_every_task_once() {
+        let sum = AtomicUsize::new(0);
+        for threads in [1, 2, 7] {
+            sum.store(0, Ordering::Relaxed);
+            for_each(1..=100, threads, |task| {
+                sum.fetch_add(task, Ordering::Relaxed);
+            });
+            assert_eq!(sum.load(Ordering::Relaxed), 5_050);
+        }
+    }
+
+    #[test]
+    fn join_propagates_the_original_panic() {
+        let outcome = panic::catch_unwind(|| join(|| panic!("left"), || 1));
+        let payload = outcome.unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"left"));
+    }
+}
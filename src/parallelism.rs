@@ -0,0 +1,45 @@
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for super::Sequential {}
+    impl Sealed for super::Parallel {}
+}
+
+/// Where a [`QuickSorter`](crate::QuickSorter) or a [`MergeSorter`](crate::MergeSorter) sorts the
+/// parts of the slice, implemented by [`Sequential`] and [`Parallel`].
+///
+/// Handing parts to other threads requires `T: Send`, which is why the choice is part of the
+/// type: the [`Sequential`] sorters sort any `T: Ord`.
+pub trait Parallelism: Copy + sealed::Sealed {}
+
+/// Sorts every part on the current thread.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sequential;
+
+/// Sorts the parts as tasks on the current [pool](crate::pool).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parallel {
+    /// Number of threads, 0 for one per worker of the current [pool](crate::pool). With 1, the
+    /// slice is sorted on the current thread only.
+    pub threads: usize,
+    /// Length under which a part is sorted on the current thread, because handing it to another
+    /// thread would cost more than it saves.
+    pub sequential_cutoff: usize,
+}
+
+impl Parallel {
+    /// Default [`sequential_cutoff`](Parallel::sequential_cutoff).
+    pub const DEFAULT_SEQUENTIAL_CUTOFF: usize = 1 << 13;
+}
+
+impl Default for Parallel {
+    /// Uses every worker of the current pool.
+    fn default() -> Self {
+        Self {
+            threads: 0,
+            sequential_cutoff: Self::DEFAULT_SEQUENTIAL_CUTOFF,
+        }
+    }
+}
+
+impl Parallelism for Sequential {}
+impl Parallelism for Parallel {}
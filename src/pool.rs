@@ -0,0 +1,535 @@
+use std::{
+    cell::{Cell, UnsafeCell},
+    collections::VecDeque,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    sync::{
+        Arc, Condvar, Mutex, MutexGuard, OnceLock, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+/// [`ThreadPool`]
+///
+/// A small work-stealing thread pool shared by the parallel sorters of the crate, so that a
+/// parallel sort does not pay for spawning threads.
+///
+/// Every worker has a deque of jobs. [`join`] pushes its second closure on the deque of the
+/// current worker and runs the first one: if no idle worker stole the second closure in the
+/// meantime, the current worker pops it back and runs it itself, otherwise it runs other jobs
+/// until the thief is done. Work is thereby only handed to other threads when they are idle,
+/// which makes fork-join recursion cheap enough to split a sort down to a few thousand elements.
+///
+/// The parallel sorters run on the pool of the thread calling them: the [global
+/// pool](ThreadPool::global), with one worker per available CPU, unless the sort is
+/// [installed](ThreadPool::install) on another pool.
+///
+/// ```
+/// use maniac::{MergeSorter, Parallel, Sorter, pool::ThreadPool};
+///
+/// let pool = ThreadPool::new(4);
+/// assert_eq!(pool.workers(), 4);
+///
+/// // Both sorts run on the same four workers, spawned once.
+/// for len in [10_000, 20_000] {
+///     let mut items: Vec<u32> = (0..len).rev().collect();
+///     let sorter = MergeSorter {
+///         parallelism: Parallel::default(),
+///     };
+///     pool.install(|| sorter.sort(&mut items));
+///     assert!(items.is_sorted());
+/// }
+///
+/// let (left, right) = pool.join(|| 1 + 1, || 2 + 2);
+/// assert_eq!((left, right), (2, 4));
+/// ```
+#[derive(Debug)]
+pub struct ThreadPool {
+    registry: Arc<Registry>,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+/// State shared by the workers of a pool.
+#[derive(Debug)]
+struct Registry {
+    /// The deque of every worker: the owner pushes and pops at the back, thieves steal at the
+    /// front.
+    deques: Vec<Mutex<VecDeque<JobRef>>>,
+    /// Jobs sent to the pool from other threads.
+    injector: Mutex<VecDeque<JobRef>>,
+    sleep: Sleep,
+    terminating: AtomicBool,
+}
+
+/// Where idle threads wait for new jobs or for a job they depend on to complete.
+///
+/// A thread reads the generation, looks for work, and only waits if the generation is unchanged:
+/// every new job and every completed job bumps it, so no wake-up is lost in between.
+#[derive(Debug, Default)]
+struct Sleep {
+    generation: Mutex<u64>,
+    condvar: Condvar,
+}
+
+thread_local! {
+    /// The registry and index of the current thread, if it is a worker.
+    static WORKER: Cell<Option<(*const Registry, usize)>> = const { Cell::new(None) };
+    /// Latch of the current thread, while it waits for a job sent to a pool it is not part of.
+    static LOCK_LATCH: LockLatch = LockLatch::default();
+}
+
+/// Locks `mutex`. No code of the crate's users ever runs under these locks, so they cannot be
+/// poisoned by a panicking job.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Sleep {
+    fn generation(&self) -> u64 {
+        *lock(&self.generation)
+    }
+
+    /// Waits until the next wake-up, unless there was one since `seen` was read. Callers check
+    /// their condition again afterwards, since any wake-up ends the wait.
+    fn wait(&self, seen: u64) {
+        let generation = lock(&self.generation);
+        if *generation == seen {
+            let _guard = self
+                .condvar
+                .wait(generation)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    fn wake_one(&self) {
+        *lock(&self.generation) += 1;
+        self.condvar.notify_one();
+    }
+
+    fn wake_all(&self) {
+        *lock(&self.generation) += 1;
+        self.condvar.notify_all();
+    }
+}
+
+impl Registry {
+    fn push(&self, index: usize, job: JobRef) {
+        lock(&self.deques[index]).push_back(job);
+        self.sleep.wake_one();
+    }
+
+    fn inject(&self, job: JobRef) {
+        lock(&self.injector).push_back(job);
+        self.sleep.wake_one();
+    }
+
+    /// Takes a job for worker `index`: its own newest job, or else the oldest job sent to the
+    /// pool, or else the oldest job of another worker.
+    fn find_work(&self, index: usize) -> Option<JobRef> {
+        if let Some(job) = lock(&self.deques[index]).pop_back() {
+            return Some(job);
+        }
+        if let Some(job) = lock(&self.injector).pop_front() {
+            return Some(job);
+        }
+        let workers = self.deques.len();
+        (1..workers).find_map(|offset| lock(&self.deques[(index + offset) % workers]).pop_front())
+    }
+
+    /// Runs jobs on worker `index` until `done` returns `true`, sleeping when there are none.
+    fn work_until(&self, index: usize, done: impl Fn() -> bool) {
+        while !done() {
+            let seen = self.sleep.generation();
+            if done() {
+                return;
+            }
+            match self.find_work(index) {
+                // SAFETY: a job is executed once, by whoever took it from a queue, and its owner
+                // keeps it alive until it has been executed.
+                Some(job) => unsafe { job.execute() },
+                None => self.sleep.wait(seen),
+            }
+        }
+    }
+}
+
+fn main_loop(registry: &Arc<Registry>, index: usize) {
+    WORKER.set(Some((Arc::as_ptr(registry), index)));
+    registry.work_until(index, || registry.terminating.load(Ordering::Acquire));
+}
+
+impl ThreadPool {
+    /// Spawns a pool of `workers` threads, or one per available CPU if `workers` is 0.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a thread cannot be spawned.
+    #[must_use]
+    pub fn new(workers: usize) -> Self {
+        let workers = if workers == 0 {
+            thread::available_parallelism().map_or(1, NonZeroUsize::get)
+        } else {
+            workers
+        };
+        let registry = Arc::new(Registry {
+            deques: (0..workers).map(|_| Mutex::default()).collect(),
+            injector: Mutex::default(),
+            sleep: Sleep::default(),
+            terminating: AtomicBool::new(false),
+        });
+        let handles = (0..workers)
+            .map(|index| {
+                let registry = Arc::clone(&registry);
+                thread::Builder::new()
+                    .name(format!("maniac-worker-{index}"))
+                    .spawn(move || main_loop(&registry, index))
+                    .expect("failed to spawn a pool worker")
+            })
+            .collect();
+        Self { registry, handles }
+    }
+
+    /// The pool of the parallel sorters called outside of any pool, with one worker per
+    /// available CPU, spawned on first use.
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<ThreadPool> = OnceLock::new();
+        GLOBAL.get_or_init(|| Self::new(0))
+    }
+
+    /// Number of worker threads of the pool.
+    #[must_use]
+    pub fn workers(&self) -> usize {
+        self.registry.deques.len()
+    }
+
+    /// Runs `f` on a worker of the pool and returns its result, so that the parallel sorters and
+    /// [`join`]s called by `f` run on this pool. Blocks the current thread until `f` is done,
+    /// unless it already is a worker of the pool, in which case `f` runs directly.
+    ///
+    /// A panic of `f` is propagated to the caller, the pool keeps working.
+    pub fn install<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R + Send,
+        R: Send,
+    {
+        if WORKER
+            .get()
+            .is_some_and(|(registry, _)| ptr::eq(registry, Arc::as_ptr(&self.registry)))
+        {
+            return f();
+        }
+        LOCK_LATCH.with(|latch| {
+            latch.reset();
+            let job = StackJob::new(latch, f);
+            // SAFETY: the job stays on this stack until its latch is set, after it has run.
+            self.registry.inject(unsafe { job.as_job_ref() });
+            latch.wait();
+            job.into_result()
+        })
+    }
+
+    /// Runs `a` and `b` on the pool, potentially in parallel, and returns both results. See
+    /// [`join`].
+    pub fn join<A, B, RA, RB>(&self, a: A, b: B) -> (RA, RB)
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        self.install(|| join(a, b))
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.registry.terminating.store(true, Ordering::Release);
+        self.registry.sleep.wake_all();
+        for handle in self.handles.drain(..) {
+            // Jobs catch their panics, so a worker never panics.
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Runs `a` and `b`, potentially in parallel, and returns both results once both are done.
+///
+/// On a worker of a [`ThreadPool`], `b` is offered to the other workers while the current one
+/// runs `a`. Elsewhere, the whole call is sent to the [global pool](ThreadPool::global).
+///
+/// If either closure panics, the panic is propagated once both are done, `a`'s first.
+///
+/// ```
+/// fn sum(values: &[u64]) -> u64 {
+///     if values.len() <= 1_000 {
+///         return values.iter().sum();
+///     }
+///     let (left, right) = values.split_at(values.len() / 2);
+///     let (left, right) = maniac::pool::join(|| sum(left), || sum(right));
+///     left + right
+/// }
+///
+/// let values: Vec<u64> = (1..=100_000).collect();
+/// assert_eq!(sum(&values), 5_000_050_000);
+/// ```
+pub fn join<A, B, RA, RB>(a: A, b: B) -> (RA, RB)
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    let Some((registry, index)) = WORKER.get() else {
+        return ThreadPool::global().install(|| join(a, b));
+    };
+    // SAFETY: a worker's registry lives as long as the worker, which is the current thread.
+    let registry = unsafe { &*registry };
+
+    let job_b = StackJob::new(SpinLatch::new(registry), b);
+    // SAFETY: the job stays on this stack until its latch is set, which is awaited below even if
+    // `a` panics.
+    registry.push(index, unsafe { job_b.as_job_ref() });
+    let result_a = panic::catch_unwind(AssertUnwindSafe(a));
+    // The newest job of the deque is `b` unless a thief took it: either way, the loop runs it or
+    // other jobs until it is done.
+    registry.work_until(index, || job_b.latch.probe());
+
+    match result_a {
+        Ok(result_a) => (result_a, job_b.into_result()),
+        Err(payload) => panic::resume_unwind(payload),
+    }
+}
+
+/// Number of workers of the pool the parallel sorters use on the current thread.
+#[must_use]
+pub fn current_workers() -> usize {
+    match WORKER.get() {
+        // SAFETY: a worker's registry lives as long as the worker, which is the current thread.
+        Some((registry, _)) => unsafe { (*registry).deques.len() },
+        None => ThreadPool::global().workers(),
+    }
+}
+
+/// Type-erased pointer to a [`StackJob`].
+#[derive(Debug, Clone, Copy)]
+struct JobRef {
+    data: *const (),
+    execute: unsafe fn(*const ()),
+}
+
+// SAFETY: the closure and result of a job are `Send`, which `StackJob::as_job_ref` requires.
+unsafe impl Send for JobRef {}
+
+impl JobRef {
+    /// # Safety
+    ///
+    /// The job must be alive and not executed yet.
+    unsafe fn execute(self) {
+        unsafe { (self.execute)(self.data) }
+    }
+}
+
+/// Signals that a job has been executed.
+trait Latch {
+    /// # Safety
+    ///
+    /// `this` must be valid. It may be freed by the owner of the job as soon as the latch is set,
+    /// so the implementation must not access it afterwards.
+    unsafe fn set(this: *const Self);
+}
+
+/// Latch of a job whose owner is a worker, which keeps running jobs while it waits.
+struct SpinLatch<'r> {
+    done: AtomicBool,
+    registry: &'r Registry,
+}
+
+impl<'r> SpinLatch<'r> {
+    fn new(registry: &'r Registry) -> Self {
+        Self {
+            done: AtomicBool::new(false),
+            registry,
+        }
+    }
+
+    fn probe(&self) -> bool {
+        self.done.load(Ordering::Acquire)
+    }
+}
+
+impl Latch for SpinLatch<'_> {
+    unsafe fn set(this: *const Self) {
+        // SAFETY: `this` is valid until `done` is stored, the registry outlives the job.
+        unsafe {
+            let registry = (*this).registry;
+            (*this).done.store(true, Ordering::Release);
+            registry.sleep.wake_all();
+        }
+    }
+}
+
+/// Latch of a job whose owner is not a worker of the pool, and blocks until it is done.
+#[derive(Debug, Default)]
+struct LockLatch {
+    done: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl LockLatch {
+    fn reset(&self) {
+        *lock(&self.done) = false;
+    }
+
+    fn wait(&self) {
+        let mut done = lock(&self.done);
+        while !*done {
+            done = self
+                .condvar
+                .wait(done)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+}
+
+// The latch itself is thread-local to the owner: only the reference to it lives in the job.
+impl Latch for &LockLatch {
+    unsafe fn set(this: *const Self) {
+        // SAFETY: `this` is valid until dereferenced, and the latch until the guard is dropped:
+        // the owner cannot get past `wait` before locking the mutex again.
+        let latch: &LockLatch = unsafe { *this };
+        let mut done = lock(&latch.done);
+        *done = true;
+        // Notifying under the lock keeps the owner from returning, and its thread from exiting
+        // and destroying the latch, before the notification is sent.
+        latch.condvar.notify_all();
+        drop(done);
+    }
+}
+
+/// A job living on the stack of its owner, which waits for it to be executed before returning.
+struct StackJob<L, F, R> {
+    latch: L,
+    func: UnsafeCell<Option<F>>,
+    result: UnsafeCell<Option<thread::Result<R>>>,
+}
+
+impl<L: Latch, F: FnOnce() -> R + Send, R: Send> StackJob<L, F, R> {
+    fn new(latch: L, func: F) -> Self {
+        Self {
+            latch,
+            func: UnsafeCell::new(Some(func)),
+            result: UnsafeCell::new(None),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The job must not move nor be dropped before its latch is set.
+    unsafe fn as_job_ref(&self) -> JobRef {
+        JobRef {
+            data: ptr::from_ref(self).cast(),
+            execute: Self::execute,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `data` must point to a live job of this type, executed only once.
+    unsafe fn execute(data: *const ()) {
+        let this = data.cast::<Self>();
+        // SAFETY: only the executing thread touches the closure and the result until the latch
+        // is set, and nothing touches the job afterwards.
+        unsafe {
+            let func = (*(*this).func.get()).take().expect("job executed twice");
+            *(*this).result.get() = Some(panic::catch_unwind(AssertUnwindSafe(func)));
+            L::set(&raw const (*this).latch);
+        }
+    }
+
+    /// The result of the executed job, or its panic propagated.
+    fn into_result(self) -> R {
+        match self.result.into_inner().expect("job not executed") {
+            Ok(result) => result,
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, sync::atomic::AtomicUsize};
+
+    fn fibonacci(n: u64) -> u64 {
+        if n < 2 {
+            return n;
+        }
+        let (a, b) = join(|| fibonacci(n - 1), || fibonacci(n - 2));
+        a + b
+    }
+
+    #[test]
+    fn nested_joins() {
+        let pool = ThreadPool::new(4);
+        assert_eq!(pool.install(|| fibonacci(20)), 6_765);
+        assert_eq!(fibonacci(15), 610);
+    }
+
+    #[test]
+    fn install_runs_on_the_workers() {
+        let pool = ThreadPool::new(3);
+        let names: HashSet<String> = (0..20)
+            .map(|_| pool.install(|| thread::current().name().unwrap().to_string()))
+            .collect();
+        assert!(
+            names.iter().all(|name| name.starts_with("maniac-worker-")),
+            "{names:?}"
+        );
+        assert_eq!(pool.install(current_workers), 3);
+    }
+
+    #[test]
+    fn workers_are_reused() {
+        let pool = ThreadPool::new(2);
+        let threads = Mutex::new(HashSet::new());
+        let record = || {
+            lock(&threads).insert(thread::current().id());
+        };
+        for _ in 0..50 {
+            pool.join(record, record);
+        }
+        assert!(lock(&threads).len() <= 2);
+    }
+
+    #[test]
+    fn panics_propagate_and_the_pool_survives() {
+        let pool = ThreadPool::new(2);
+        let ran = AtomicUsize::new(0);
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.join(
+                || {
+                    ran.fetch_add(1, Ordering::Relaxed);
+                },
+                || panic!("right"),
+            )
+        }));
+        assert_eq!(outcome.unwrap_err().downcast_ref::<&str>(), Some(&"right"));
+        assert_eq!(ran.load(Ordering::Relaxed), 1);
+
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            pool.join(|| panic!("left"), || panic!("right"))
+        }));
+        assert_eq!(outcome.unwrap_err().downcast_ref::<&str>(), Some(&"left"));
+
+        assert_eq!(pool.join(|| 1, || 2), (1, 2));
+    }
+
+    #[test]
+    fn dropping_the_pool_stops_the_workers() {
+        let pool = ThreadPool::new(3);
+        assert_eq!(pool.install(|| fibonacci(10)), 55);
+        drop(pool);
+    }
+}
@@ -0,0 +1,187 @@
+use super::{InsertionSorter, Parallel, Parallelism, Sequential, Sorter, Swap, parallel};
+
+/// [`QuickSorter`]
+///
+/// The [*quicksort*](https://en.wikipedia.org/wiki/Quicksort) picks a pivot, the median of the
+/// first, middle and last elements, and partitions the slice around it with Hoare's scheme: the
+/// elements not greater than the pivot go before it, those not less after it, and both parts are
+/// sorted recursively. Elements equal to the pivot end up on both sides, so duplicates still
+/// split the slice evenly. Short subslices are sorted by an insertion sort.
+///
+/// On the current thread, the shorter part is sorted recursively and the longer one
+/// iteratively, which bounds the stack to *O(log n)*. With [`Parallel`], the two parts of a
+/// subslice longer than its [`sequential_cutoff`](Parallel::sequential_cutoff) are sorted as two
+/// tasks on the current [pool](crate::pool), until every thread has one. Handing parts to other
+/// threads requires `T: Send`, which is why the choice is part of the type: the default
+/// [`Sequential`] sorter sorts any `T: Ord`.
+///
+/// Elements are only ever swapped, so a panicking comparison leaves the slice a permutation.
+///
+/// ```
+/// use maniac::{Parallel, QuickSorter, Sorter};
+///
+/// let mut items: Vec<u32> = (0..100_000).map(|i| i * 7_919 % 1_000).collect();
+/// QuickSorter {
+///     parallelism: Parallel {
+///         threads: 4,
+///         sequential_cutoff: 1_000,
+///     },
+/// }
+/// .sort(&mut items);
+///
+/// assert!(items.is_sorted());
+/// ```
+///
+/// ### Complexity
+/// | Best | Average | Worst | Space | In-place | Stable |
+/// | ---- | ------- | ----- | ----- | -------- | ------ |
+/// | *O(n log n)* | *O(n log n)* | *O(n<sup>2</sup>)* | *O(log n)* | Yes | No |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuickSorter<P: Parallelism = Sequential> {
+    /// Whether the partitions are sorted across the workers of the current pool.
+    pub parallelism: P,
+}
+
+impl Default for QuickSorter {
+    fn default() -> Self {
+        Self {
+            parallelism: Sequential,
+        }
+    }
+}
+
+/// Length under which subslices are sorted by an insertion sort.
+const INSERTION_LEN: usize = 16;
+
+impl<T: Ord> Sorter<T> for QuickSorter<Sequential> {
+    fn sort(self, slice: &mut [T]) {
+        quick_sort(slice);
+    }
+}
+
+impl<T: Ord + Send> Sorter<T> for QuickSorter<Parallel> {
+    fn sort(self, slice: &mut [T]) {
+        parallel_quick_sort(
+            slice,
+            parallel::threads(self.parallelism.threads),
+            self.parallelism.sequential_cutoff,
+        );
+    }
+}
+
+/// Sorts `slice` on the current thread.
+fn quick_sort<T: Ord>(mut slice: &mut [T]) {
+    loop {
+        if slice.len() <= INSERTION_LEN {
+            InsertionSorter { method: Swap }.sort(slice);
+            return;
+        }
+        let (less, greater) = partition(slice);
+        if less.len() < greater.len() {
+            quick_sort(less);
+            slice = greater;
+        } else {
+            quick_sort(greater);
+            slice = less;
+        }
+    }
+}
+
+/// Sorts `slice` over `threads` threads.
+fn parallel_quick_sort<T: Ord + Send>(slice: &mut [T], threads: usize, cutoff: usize) {
+    if threads < 2 || slice.len() <= cutoff.max(INSERTION_LEN) {
+        quick_sort(slice);
+        return;
+    }
+    let (less, greater) = partition(slice);
+    let less_threads = threads / 2;
+    parallel::join(
+        || parallel_quick_sort(less, less_threads, cutoff),
+        || parallel_quick_sort(greater, threads - less_threads, cutoff),
+    );
+}
+
+/// Partitions `slice`, of at least 3 elements, around the median of its first, middle and last
+/// elements, and returns the elements before it and those after it: the pivot is left between
+/// the two, in its final place.
+///
+/// Every index stays in bounds and both returned parts are shorter than `slice`, whatever the
+/// `Ord` implementation does.
+fn partition<T: Ord>(slice: &mut [T]) -> (&mut [T], &mut [T]) {
+    let (mid, last) = (slice.len() / 2, slice.len() - 1);
+    if slice[mid] < slice[0] {
+        slice.swap(0, mid);
+    }
+    if slice[last] < slice[mid] {
+        slice.swap(mid, last);
+        if slice[mid] < slice[0] {
+            slice.swap(0, mid);
+        }
+    }
+    slice.swap(0, mid);
+
+    // `[1..left]` is not greater than the pivot and `[right + 1..]` not less. Elements equal to
+    // the pivot stop both scans and are swapped, which splits runs of duplicates evenly.
+    let (mut left, mut right) = (1, last);
+    loop {
+        while left <= right && slice[left] < slice[0] {
+            left += 1;
+        }
+        while left <= right && slice[0] < slice[right] {
+            right -= 1;
+        }
+        if left >= right {
+            break;
+        }
+        slice.swap(left, right);
+        left += 1;
+        right -= 1;
+    }
+    let pivot = left - 1;
+    slice.swap(0, pivot);
+    let (before, rest) = slice.split_at_mut(pivot);
+    (before, &mut rest[1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorter_tests;
+
+    sorter_tests!(quick, QuickSorter::default());
+    sorter_tests!(
+        quick_parallel,
+        QuickSorter {
+            parallelism: Parallel::default()
+        }
+    );
+    // Every subslice is split over the threads, to exercise the parallel paths on short inputs.
+    sorter_tests!(
+        quick_parallel_no_cutoff,
+        QuickSorter {
+            parallelism: Parallel {
+                threads: 4,
+                sequential_cutoff: 0,
+            }
+        }
+    );
+
+    #[test]
+    fn sequential_quick_sort_does_not_need_send() {
+        use std::rc::Rc;
+
+        let mut items: Vec<Rc<i32>> = (0..100).rev().map(Rc::new).collect();
+        QuickSorter::default().sort(&mut items);
+        assert!(items.is_sorted());
+    }
+
+    #[test]
+    fn partition_places_the_pivot() {
+        let mut items = [5, 1, 5, 9, 5, 0, 7, 5, 3];
+        let (before, after) = partition(&mut items);
+        assert!(before.iter().all(|&item| item <= 5), "{before:?}");
+        assert!(after.iter().all(|&item| item >= 5), "{after:?}");
+        let pivot = before.len();
+        assert_eq!(items[pivot], 5);
+    }
+}
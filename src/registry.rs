@@ -0,0 +1,370 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::fmt;
+
+use super::{
+    BubbleSorter, CocktailShakerSorter, CombSorter, Finish, GnomeSorter, InsertionSorter,
+    MergeSorter, Metadata, OddEvenSorter, Optimization, Parallel, ParallelPhases, QuickSorter,
+    RotateStable, RotateUnstable, SampleSorter, Sorter, SorterMetadata, StandardSorter, Swap,
+};
+
+/// [`DynSorter`]
+///
+/// Object-safe companion of [`Sorter`]. [`Sorter::sort`] consumes the sorter, which prevents
+/// storing sorters as trait objects; [`DynSorter::sort_dyn`] borrows it instead and sorts with a
+/// clone. It is implemented for every `Clone` sorter, so sorters of different types can be kept
+/// side by side as `Box<dyn DynSorter<T>>`.
+///
+/// ```
+/// use maniac::{BubbleSorter, DynSorter, GnomeSorter};
+///
+/// let sorters: Vec<Box<dyn DynSorter<i32>>> =
+///     vec![Box::new(BubbleSorter::default()), Box::new(GnomeSorter)];
+///
+/// for sorter in &sorters {
+///     let mut items = vec![3, 1, 2];
+///     sorter.sort_dyn(&mut items);
+///     assert_eq!(items, vec![1, 2, 3]);
+/// }
+/// ```
+pub trait DynSorter<T> {
+    /// Sorts `slice` with a clone of `self`.
+    fn sort_dyn(&self, slice: &mut [T]);
+
+    /// Returns a boxed clone of `self`, which makes `Box<dyn DynSorter<T>>` [`Clone`].
+    fn clone_box<'a>(&self) -> Box<dyn DynSorter<T> + 'a>
+    where
+        Self: 'a;
+}
+
+impl<T, S> DynSorter<T> for S
+where
+    T: Ord,
+    S: Sorter<T> + Clone,
+{
+    fn sort_dyn(&self, slice: &mut [T]) {
+        self.clone().sort(slice);
+    }
+
+    fn clone_box<'a>(&self) -> Box<dyn DynSorter<T> + 'a>
+    where
+        Self: 'a,
+    {
+        Box::new(self.clone())
+    }
+}
+
+impl<'a, T: 'a> Clone for Box<dyn DynSorter<T> + 'a> {
+    fn clone(&self) -> Self {
+        (**self).clone_box()
+    }
+}
+
+impl<T: Ord> Sorter<T> for &dyn DynSorter<T> {
+    fn sort(self, slice: &mut [T]) {
+        self.sort_dyn(slice);
+    }
+}
+
+impl<T: Ord> Sorter<T> for Box<dyn DynSorter<T>> {
+    fn sort(self, slice: &mut [T]) {
+        (*self).sort_dyn(slice);
+    }
+}
+
+/// An algorithm of the [`registry`](crate::registry) in one of its configurations.
+pub struct RegisteredSorter<T> {
+    /// Name of the algorithm, e.g. `"insertion"`.
+    pub name: &'static str,
+    /// Name of the configuration of the algorithm, e.g. `"rotate_stable"`.
+    pub variant: &'static str,
+    /// One-line description of the configuration.
+    pub description: &'static str,
+    /// Stability, complexity and resource usage of the configuration.
+    pub metadata: Metadata,
+    /// The configured sorter.
+    pub sorter: Box<dyn DynSorter<T>>,
+}
+
+impl<T> RegisteredSorter<T> {
+    fn new<S>(
+        name: &'static str,
+        variant: &'static str,
+        description: &'static str,
+        sorter: S,
+    ) -> Self
+    where
+        S: DynSorter<T> + SorterMetadata + 'static,
+    {
+        Self {
+            name,
+            variant,
+            description,
+            metadata: sorter.metadata(),
+            sorter: Box::new(sorter),
+        }
+    }
+}
+
+impl<T> fmt::Debug for RegisteredSorter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegisteredSorter")
+            .field("name", &self.name)
+            .field("variant", &self.variant)
+            .field("description", &self.description)
+            .field("metadata", &self.metadata)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns every sorting algorithm of the crate, once per configuration.
+///
+/// The adapters are left out: [`ReverseSorter`](crate::ReverseSorter) sorts in descending order,
+/// [`FloatSorter`](crate::FloatSorter) sorts floats, which are not `Ord`, and
+/// [`Spec`](crate::spec::Spec) and [`HybridSorter`](crate::HybridSorter) only configure or combine
+/// the sorters listed here.
+///
+/// ```
+/// let names: Vec<String> = maniac::registry::all::<i32>()
+///     .iter()
+///     .map(|entry| format!("{}/{}", entry.name, entry.variant))
+///     .collect();
+///
+/// assert!(names.contains(&"insertion/swap".to_string()));
+/// ```
+#[must_use]
+pub fn all<T: Ord + Send + Sync>() -> Vec<RegisteredSorter<T>> {
+    let mut all = vec![RegisteredSorter::new(
+        "standard",
+        "default",
+        "The standard library's slice::sort",
+        StandardSorter,
+    )];
+    all.extend(exchange_sorters());
+    all.extend(insertion_sorters());
+    all.extend(divide_and_conquer_sorters());
+    all
+}
+
+/// The sorters swapping elements that compare in the wrong order: bubble, cocktail shaker, comb,
+/// gnome and odd-even sorts.
+fn exchange_sorters<T: Ord + Send>() -> Vec<RegisteredSorter<T>> {
+    vec![
+        RegisteredSorter::new(
+            "bubble",
+            "new_len",
+            "Bubble sort stopping each pass at the last swap",
+            BubbleSorter {
+                optimization: Optimization::NewLen,
+            },
+        ),
+        RegisteredSorter::new(
+            "bubble",
+            "len_minus_one",
+            "Bubble sort shortening each pass by one",
+            BubbleSorter {
+                optimization: Optimization::LenMinusOne,
+            },
+        ),
+        RegisteredSorter::new(
+            "bubble",
+            "none",
+            "Textbook bubble sort scanning the whole slice on every pass",
+            BubbleSorter {
+                optimization: Optimization::None,
+            },
+        ),
+        RegisteredSorter::new(
+            "cocktail_shaker",
+            "default",
+            "Bidirectional bubble sort",
+            CocktailShakerSorter,
+        ),
+        RegisteredSorter::new(
+            "comb",
+            "default",
+            "Combsort11 with a shrink factor of 1.3",
+            CombSorter::default(),
+        ),
+        RegisteredSorter::new(
+            "comb",
+            "plain",
+            "Comb sort with a shrink factor of 1.3, without the rule of 11",
+            CombSorter::default().with_rule_of_11(false),
+        ),
+        RegisteredSorter::new(
+            "comb",
+            "final_insertion",
+            "Combsort11 finished by an insertion sort instead of bubble passes",
+            CombSorter::default().with_finish(Finish::Insertion),
+        ),
+        RegisteredSorter::new("gnome", "default", "Gnome sort", GnomeSorter),
+        RegisteredSorter::new(
+            "odd_even",
+            "sequential",
+            "Odd-even transposition sort on the current thread",
+            OddEvenSorter::default(),
+        ),
+        RegisteredSorter::new(
+            "odd_even",
+            "parallel",
+            "Odd-even transposition sort with phases split across all CPUs",
+            OddEvenSorter {
+                phases: ParallelPhases,
+            },
+        ),
+    ]
+}
+
+/// The insertion sorters.
+fn insertion_sorters<T: Ord + Send>() -> Vec<RegisteredSorter<T>> {
+    vec![
+        RegisteredSorter::new(
+            "insertion",
+            "rotate_stable",
+            "Binary insertion sort rotating after the upper bound",
+            InsertionSorter {
+                method: RotateStable,
+            },
+        ),
+        RegisteredSorter::new(
+            "insertion",
+            "rotate_unstable",
+            "Binary insertion sort rotating after the lower bound",
+            InsertionSorter {
+                method: RotateUnstable,
+            },
+        ),
+        RegisteredSorter::new(
+            "insertion",
+            "swap",
+            "Insertion sort with adjacent swaps",
+            InsertionSorter { method: Swap },
+        ),
+    ]
+}
+
+/// The *O(n log n)* sorters splitting the slice: merge, quick and sample sorts.
+fn divide_and_conquer_sorters<T: Ord + Send + Sync>() -> Vec<RegisteredSorter<T>> {
+    vec![
+        RegisteredSorter::new(
+            "merge",
+            "sequential",
+            "Merge sort on the current thread",
+            MergeSorter::default(),
+        ),
+        RegisteredSorter::new(
+            "merge",
+            "parallel",
+            "Merge sort with halves sorted and merged across all CPUs",
+            MergeSorter {
+                parallelism: Parallel::default(),
+            },
+        ),
+        RegisteredSorter::new(
+            "quick",
+            "sequential",
+            "Median-of-three quicksort on the current thread",
+            QuickSorter::default(),
+        ),
+        RegisteredSorter::new(
+            "quick",
+            "parallel",
+            "Median-of-three quicksort with partitions sorted across all CPUs",
+            QuickSorter {
+                parallelism: Parallel::default(),
+            },
+        ),
+        RegisteredSorter::new(
+            "sample",
+            "parallel",
+            "Sample sort with buckets classified and quicksorted across all CPUs",
+            SampleSorter::default(),
+        ),
+    ]
+}
+
+/// Returns the registered sorter called `name` in its `variant` configuration, if any.
+#[must_use]
+pub fn find<T: Ord + Send + Sync>(name: &str, variant: &str) -> Option<RegisteredSorter<T>> {
+    all()
+        .into_iter()
+        .find(|entry| entry.name == name && entry.variant == variant)
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cmp::Reverse;
+
+    use super::*;
+    use crate::{Complexity, Descending, MIN_PAIRS_PER_THREAD};
+
+    #[test]
+    fn entries_are_unique() {
+        let entries = all::<i32>();
+        for (i, entry) in entries.iter().enumerate() {
+            assert!(
+                entries[i + 1..]
+                    .iter()
+                    .all(|other| (other.name, other.variant) != (entry.name, entry.variant)),
+                "{}/{} is registered twice",
+                entry.name,
+                entry.variant
+            );
+        }
+    }
+
+    #[test]
+    fn every_entry_sorts() {
+        for entry in all::<i32>() {
+            let mut data = vec![5, 3, 8, 1, 9, 2, 7, 3, 0, 6];
+            entry.sorter.sort_dyn(&mut data);
+            assert_eq!(data, vec![0, 1, 2, 3, 3, 5, 6, 7, 8, 9], "{entry:?}");
+        }
+    }
+
+    #[test]
+    fn every_parallel_entry_sorts_across_threads() {
+        let mut rng = crate::dataset::Rng::new(28);
+        for entry in all::<usize>().into_iter().filter(|e| e.metadata.parallel) {
+            // Long enough for the sorter to hand work to at least two threads.
+            let len = if entry.metadata.average == Complexity::Quadratic {
+                4 * MIN_PAIRS_PER_THREAD
+            } else {
+                2 * Parallel::DEFAULT_SEQUENTIAL_CUTOFF
+            };
+            let mut data: Vec<usize> = (0..len).map(|_| rng.below(len)).collect();
+            let mut expected = data.clone();
+            expected.sort_unstable();
+
+            entry.sorter.sort_dyn(&mut data);
+            assert!(data == expected, "{entry:?}");
+        }
+    }
+
+    #[test]
+    fn every_entry_sorts_descending() {
+        for entry in all::<Reverse<i32>>() {
+            let mut data = vec![5, 3, 8, 1, 9, 2, 7, 3, 0, 6];
+            (&*entry.sorter).descending().sort(&mut data);
+            assert_eq!(data, vec![9, 8, 7, 6, 5, 3, 3, 2, 1, 0], "{entry:?}");
+        }
+    }
+
+    #[test]
+    fn boxed_sorters_are_sorters() {
+        let sorter: Box<dyn DynSorter<i32>> = Box::new(GnomeSorter);
+        let mut data = vec![2, 3, 1];
+        sorter.sort(&mut data);
+        assert_eq!(data, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn find_by_name_and_variant() {
+        let entry = find::<&str>("insertion", "swap").unwrap();
+        let mut data = vec!["b", "c", "a"];
+        entry.sorter.sort_dyn(&mut data);
+        assert_eq!(data, vec!["a", "b", "c"]);
+
+        assert!(find::<i32>("insertion", "unknown").is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use core::cmp::Reverse;
+
+use super::{Sorter, StableSorter};
+
+/// [`ReverseSorter`]
+///
+/// Adapter that sorts a slice in **descending** order with any wrapped [`Sorter`], without having
+/// to wrap every element in [`core::cmp::Reverse`] by hand.
+///
+/// The slice is viewed as a slice of [`Reverse<T>`] (which has the exact same layout as `T`) and
+/// handed to the wrapped sorter, so the adapter costs nothing at runtime. Elements that compare
+/// equal are never reordered by the adapter itself: if the wrapped sorter is stable, equal
+/// elements keep their original relative order in descending mode too.
+///
+/// ```
+/// use maniac::{Descending, InsertionSorter, Sorter};
+///
+/// let mut items = vec![2, 5, 1, 4, 3];
+/// InsertionSorter::default().descending().sort(&mut items);
+///
+/// assert_eq!(items, vec![5, 4, 3, 2, 1]);
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReverseSorter<S> {
+    /// The sorter doing the actual work on the reversed view of the slice.
+    pub sorter: S,
+}
+
+impl<S> ReverseSorter<S> {
+    /// Wraps `sorter` so that it sorts in descending order.
+    pub fn new(sorter: S) -> Self {
+        Self { sorter }
+    }
+}
+
+impl<T, S> Sorter<T> for ReverseSorter<S>
+where
+    T: Ord,
+    S: Sorter<Reverse<T>>,
+{
+    fn sort(self, slice: &mut [T]) {
+        let len = slice.len();
+        // SAFETY: `Reverse<T>` is `#[repr(transparent)]` over `T`, so a `[T]` and a
+        // `[Reverse<T>]` of the same length have the same layout. The reborrow is exclusive and
+        // lives no longer than `slice`.
+        let reversed = unsafe {
+            core::slice::from_raw_parts_mut(slice.as_mut_ptr().cast::<Reverse<T>>(), len)
+        };
+        self.sorter.sort(reversed);
+    }
+}
+
+impl<T, S> StableSorter<T> for ReverseSorter<S>
+where
+    T: Ord,
+    S: StableSorter<Reverse<T>>,
+{
+}
+
+/// Extension trait giving sorters a [`descending`](Descending::descending) constructor.
+///
+/// It is implemented for every type, so that any [`Sorter`], including those of other crates and
+/// the trait objects of the [`registry`](crate::registry), gets the method without any
+/// implementation of its own. The [`ReverseSorter`] it returns only sorts if the wrapped value is
+/// a [`Sorter`].
+///
+/// ```
+/// use maniac::{Descending, Sorter};
+///
+/// struct MySorter;
+///
+/// impl<T: Ord> Sorter<T> for MySorter {
+///     fn sort(self, slice: &mut [T]) {
+///         slice.sort();
+///     }
+/// }
+///
+/// let mut items = [1, 3, 2];
+/// MySorter.descending().sort(&mut items);
+/// assert_eq!(items, [3, 2, 1]);
+/// ```
+///
+/// [`FloatSorter`](crate::FloatSorter) is not a [`Sorter`], as floats are not `Ord`: wrap a
+/// descending sorter in it instead, keeping in mind that this reverses its
+/// [`NanPolicy`](crate::NanPolicy) too.
+pub trait Descending: Sized {
+    /// Wraps `self` in a [`ReverseSorter`] so that it sorts in descending order.
+    fn descending(self) -> ReverseSorter<Self> {
+        ReverseSorter::new(self)
+    }
+}
+
+impl<S> Descending for S {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        BubbleSorter, CocktailShakerSorter, CombSorter, GnomeSorter, InsertionSorter,
+        OddEvenSorter, Swap, sorter_stability_tests,
+    };
+
+    sorter_stability_tests!(
+        reverse_insertion_stability,
+        InsertionSorter::default().descending()
+    );
+    sorter_stability_tests!(
+        reverse_bubble_stability,
+        BubbleSorter::default().descending()
+    );
+    sorter_stability_tests!(
+        reverse_insertion_swap_stability,
+        InsertionSorter { method: Swap }.descending()
+    );
+
+    fn assert_descending<S: Sorter<Reverse<i32>> + Copy>(sorter: S) {
+        for data in [
+            vec![],
+            vec![42],
+            vec![1, 2, 3, 4, 5],
+            vec![5, 4, 3, 2, 1],
+            vec![3, 1, 2, 3, 2, 1],
+        ] {
+            let mut expected = data.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+
+            let mut data = data;
+            sorter.descending().sort(&mut data);
+            assert_eq!(data, expected);
+        }
+    }
+
+    #[test]
+    fn sorts_descending() {
+        assert_descending(BubbleSorter::default());
+        assert_descending(CocktailShakerSorter);
+        assert_descending(CombSorter::default());
+        assert_descending(GnomeSorter);
+        assert_descending(InsertionSorter::default());
+        assert_descending(OddEvenSorter::default());
+    }
+
+    #[test]
+    fn double_reverse_is_ascending() {
+        let mut data = vec![3, 1, 2];
+        InsertionSorter::default()
+            .descending()
+            .descending()
+            .sort(&mut data);
+        assert_eq!(data, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,253 @@
+use alloc::{vec, vec::Vec};
+
+use super::{QuickSorter, Sorter, dataset::Rng, parallel};
+
+/// [`SampleSorter`]
+///
+/// The [*sample sort*](https://en.wikipedia.org/wiki/Samplesort) generalizes quicksort to many
+/// pivots at once, which makes it the sorter of choice for large in-memory batches on many
+/// cores:
+///
+/// 1.  **Sampling**: `buckets × oversampling` random elements are moved to the front of the
+///     slice and sorted by the inner `sorter`. Every `oversampling`-th of them becomes a
+///     *splitter*, the oversampling making the buckets between the splitters even.
+/// 2.  **Classification**: every element is binary searched among the splitters, in parallel
+///     chunks. Elements equal to a splitter get a bucket of their own, which needs no sorting,
+///     so duplicates cost nothing.
+/// 3.  **Distribution**: the elements are moved to their bucket by following the cycles of the
+///     permutation, with swaps only.
+/// 4.  **Bucket sorting**: the buckets are sorted concurrently by the inner `sorter`, on the
+///     workers of the current [pool](crate::pool), the largest first.
+///
+/// Slices shorter than [`sequential_cutoff`](SampleSorter) are handed to the inner sorter as is.
+///
+/// ```
+/// use maniac::{InsertionSorter, SampleSorter, Sorter, Swap};
+///
+/// let mut items: Vec<u64> = (0..50_000).map(|i| i * 7_919 % 10_007).collect();
+/// SampleSorter {
+///     threads: 4,
+///     sequential_cutoff: 1_000,
+///     buckets: 256,
+///     ..SampleSorter::new(InsertionSorter { method: Swap })
+/// }
+/// .sort(&mut items);
+///
+/// assert!(items.is_sorted());
+/// ```
+///
+/// ### Complexity
+/// With *k* buckets, on top of the inner sorter sorting buckets of about *n / k* elements:
+///
+/// | Best | Average | Worst | Space | In-place | Stable |
+/// | ---- | ------- | ----- | ----- | -------- | ------ |
+/// | *O(n log k)* | *O(n log k)* | *O(n log k)* | *O(n)* | No | No |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleSorter<S = QuickSorter> {
+    /// The sorter of the sample and of every bucket.
+    pub sorter: S,
+    /// Number of threads, 0 for one per worker of the current [pool](crate::pool). With 1, the
+    /// slice is sorted on the current thread only.
+    pub threads: usize,
+    /// Length under which the slice is sorted by the inner sorter directly.
+    pub sequential_cutoff: usize,
+    /// Number of buckets, 0 for four per thread. Fewer are used if the slice is too short to
+    /// sample them.
+    pub buckets: usize,
+    /// Number of sampled elements per bucket.
+    pub oversampling: usize,
+}
+
+impl<S> SampleSorter<S> {
+    /// Default [`sequential_cutoff`](SampleSorter::sequential_cutoff).
+    pub const DEFAULT_SEQUENTIAL_CUTOFF: usize = 1 << 13;
+    /// Default [`oversampling`](SampleSorter::oversampling).
+    pub const DEFAULT_OVERSAMPLING: usize = 16;
+
+    /// Returns a sample sorter sorting the sample and the buckets with `sorter`, on every worker
+    /// of the current pool.
+    pub fn new(sorter: S) -> Self {
+        Self {
+            sorter,
+            threads: 0,
+            sequential_cutoff: Self::DEFAULT_SEQUENTIAL_CUTOFF,
+            buckets: 0,
+            oversampling: Self::DEFAULT_OVERSAMPLING,
+        }
+    }
+}
+
+impl Default for SampleSorter {
+    /// Sorts the buckets with a [`QuickSorter`] on the current thread of each task.
+    fn default() -> Self {
+        Self::new(QuickSorter::default())
+    }
+}
+
+impl<T, S> Sorter<T> for SampleSorter<S>
+where
+    T: Ord + Send + Sync,
+    S: Sorter<T> + Clone + Sync,
+{
+    fn sort(self, slice: &mut [T]) {
+        let threads = parallel::threads(self.threads);
+        let oversampling = self.oversampling.max(1);
+        let buckets = if self.buckets == 0 {
+            4 * threads
+        } else {
+            self.buckets
+        }
+        // At most half of the slice is sampled.
+        .min(slice.len() / 2 / oversampling);
+        if slice.len() < self.sequential_cutoff || buckets < 2 {
+            self.sorter.sort(slice);
+            return;
+        }
+
+        let sample_len = buckets * oversampling;
+        let mut rng = Rng::new(slice.len() as u64);
+        for index in 0..sample_len {
+            slice.swap(index, index + rng.below(slice.len() - index));
+        }
+        self.sorter.clone().sort(&mut slice[..sample_len]);
+
+        let ids = classify(slice, sample_len, oversampling, threads);
+        let bounds = distribute(slice, ids);
+
+        // The buckets at odd ids only hold elements equal to a splitter.
+        let mut rest = slice;
+        let mut tasks = Vec::with_capacity(bounds.len() / 2 + 1);
+        for (id, len) in bounds.into_iter().enumerate() {
+            let (bucket, tail) = rest.split_at_mut(len);
+            rest = tail;
+            if id % 2 == 0 && len > 1 {
+                tasks.push(bucket);
+            }
+        }
+        tasks.sort_unstable_by_key(|bucket| core::cmp::Reverse(bucket.len()));
+        parallel::for_each(tasks, threads, |bucket| self.sorter.clone().sort(bucket));
+    }
+}
+
+/// Returns the bucket id of every element of `slice`, whose sorted sample of `sample_len`
+/// elements is at the front. Every `oversampling`-th element of the sample is a splitter,
+/// duplicates aside. The elements between splitters `i - 1` and `i` get the id `2 i`, those
+/// equal to splitter `i` the id `2 i + 1`.
+fn classify<T: Ord + Sync>(
+    slice: &[T],
+    sample_len: usize,
+    oversampling: usize,
+    threads: usize,
+) -> Vec<u32> {
+    let mut splitters: Vec<&T> = Vec::with_capacity(sample_len / oversampling);
+    for splitter in slice[..sample_len]
+        .iter()
+        .skip(oversampling - 1)
+        .step_by(oversampling)
+    {
+        if splitters.last().is_none_or(|&last| last < splitter) {
+            splitters.push(splitter);
+        }
+    }
+
+    let mut ids = vec![0; slice.len()];
+    let chunk_len = slice.len().div_ceil(threads * 4);
+    let tasks = slice.chunks(chunk_len).zip(ids.chunks_mut(chunk_len));
+    parallel::for_each(tasks, threads, |(elements, ids): (&[T], &mut [u32])| {
+        for (element, id) in elements.iter().zip(ids) {
+            let index = splitters.partition_point(|&splitter| splitter < element);
+            let equal = splitters
+                .get(index)
+                .is_some_and(|&splitter| element >= splitter);
+            *id = u32::try_from(2 * index + usize::from(equal)).expect("too many buckets");
+        }
+    });
+    ids
+}
+
+/// Moves every element of `slice` to its bucket, the buckets being in the order of their ids,
+/// and returns the length of every bucket.
+fn distribute<T>(slice: &mut [T], mut ids: Vec<u32>) -> Vec<usize> {
+    let buckets = ids.iter().max().map_or(0, |&id| id as usize + 1);
+    let mut lens = vec![0; buckets];
+    for &id in &ids {
+        lens[id as usize] += 1;
+    }
+
+    // `next[b]` is the first place of bucket `b` not holding one of its elements yet.
+    let mut next = Vec::with_capacity(buckets);
+    let mut start = 0;
+    for &len in &lens {
+        next.push(start);
+        start += len;
+    }
+    let mut end = 0;
+    for (bucket, &len) in lens.iter().enumerate() {
+        end += len;
+        // Every swap puts the element at `next[bucket]` in its own bucket, for good.
+        while next[bucket] < end {
+            let place = next[bucket];
+            let target = ids[place] as usize;
+            if target != bucket {
+                let target_place = next[target];
+                slice.swap(place, target_place);
+                ids.swap(place, target_place);
+            }
+            next[target] += 1;
+        }
+    }
+    lens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{InsertionSorter, MergeSorter, Swap, sorter_tests};
+
+    sorter_tests!(sample, SampleSorter::default());
+    // The cutoff and bucket sizes force the sampling on short inputs.
+    sorter_tests!(
+        sample_small_buckets,
+        SampleSorter {
+            threads: 3,
+            sequential_cutoff: 0,
+            buckets: 4,
+            oversampling: 2,
+            ..SampleSorter::new(InsertionSorter { method: Swap })
+        }
+    );
+    sorter_tests!(
+        sample_sequential,
+        SampleSorter {
+            threads: 1,
+            sequential_cutoff: 0,
+            buckets: 8,
+            oversampling: 1,
+            ..SampleSorter::new(MergeSorter::default())
+        }
+    );
+
+    #[test]
+    fn distribute_groups_by_id() {
+        let mut items = ['c', 'a', 'd', 'b', 'a', 'c'];
+        let ids = items.iter().map(|&item| item as u32 - 'a' as u32).collect();
+        let lens = distribute(&mut items, ids);
+        assert_eq!(items, ['a', 'a', 'b', 'c', 'c', 'd']);
+        assert_eq!(lens, [2, 1, 2, 1]);
+    }
+
+    #[test]
+    fn duplicates_go_to_equality_buckets() {
+        let mut items: Vec<u32> = (0..10_000).map(|i| i % 3).collect();
+        let ids = classify(&items, 16, 4, 2);
+        // The sample is unsorted here, only its duplicate-free splitters matter.
+        assert!(ids.iter().all(|&id| id % 2 == 1), "{:?}", &ids[..20]);
+
+        SampleSorter {
+            sequential_cutoff: 0,
+            ..SampleSorter::default()
+        }
+        .sort(&mut items);
+        assert!(items.is_sorted());
+    }
+}
@@ -0,0 +1,1109 @@
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error, fmt, str::FromStr};
+
+use super::{
+    BubbleSorter, CocktailShakerSorter, CombSorter, Finish, GnomeSorter, HybridSorter,
+    InsertionSorter, MergeSorter, Metadata, OddEvenSorter, Optimization, Parallel, ParallelPhases,
+    QuickSorter, RotateStable, RotateUnstable, SampleSorter, Sequential, SequentialPhases, Sorter,
+    SorterMetadata, StandardSorter, Swap,
+};
+
+/// [`Spec`]
+///
+/// A fully configured sorter, chosen at runtime from a textual spec, so that benchmark matrices
+/// and experiment configurations can live in text files. A spec is the name of a sorter,
+/// followed by its arguments in parentheses if it wraps other sorters, and by its parameters
+/// after a colon:
+///
+/// ```text
+/// insertion:method=swap
+/// comb:shrink=1.25,rule11=off
+/// hybrid(comb,insertion,16)
+/// sample(insertion:method=swap):threads=4,buckets=64
+/// ```
+///
+/// | Spec | Parameters (default first) |
+/// | ---- | -------------------------- |
+/// | `standard` | |
+/// | `bubble` | `optimization=new_len\|len_minus_one\|none` |
+/// | `cocktail_shaker` | |
+/// | `comb` | `shrink=1.3` (a decimal or a ratio such as `4/3`), `rule11=on\|off`, `finish=bubble\|insertion` |
+/// | `gnome` | |
+/// | `insertion` | `method=rotate_stable\|rotate_unstable\|swap` |
+/// | `odd_even` | `parallel=off\|on` |
+/// | `merge`, `quick` | `parallel=off\|on`, then with `parallel=on`: `threads=0`, `cutoff=8192` |
+/// | `sample(sorter)` | `threads=0`, `cutoff=8192`, `buckets=0`, `oversampling=16` |
+/// | `hybrid(large,small,threshold)` | |
+///
+/// `sample` alone samples with `quick`, like [`SampleSorter::default`].
+/// Arguments nest at most 64 specs deep.
+///
+/// [`Display`](fmt::Display) prints the canonical spec, which only lists the parameters that
+/// differ from their default, and parses back to the same sorter:
+///
+/// ```
+/// use maniac::{CombSorter, Sorter, spec::Spec};
+///
+/// let spec: Spec = "comb : rule11=off, shrink=1.250".parse()?;
+/// assert_eq!(
+///     spec,
+///     Spec::Comb(
+///         CombSorter::default()
+///             .with_shrink_ratio(5, 4)?
+///             .with_rule_of_11(false)
+///     )
+/// );
+/// assert_eq!(spec.to_string(), "comb:shrink=1.25,rule11=off");
+///
+/// let mut items = vec![5, 3, 1, 4, 2];
+/// spec.sort(&mut items);
+/// assert_eq!(items, vec![1, 2, 3, 4, 5]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// A spec is a [`DynSorter`](crate::DynSorter) like any other sorter, so it can be benchmarked
+/// with [`Benchmarker::measure`](crate::bench::Benchmarker::measure) directly. As it may hand
+/// elements to other threads, it only sorts `Send + Sync` elements: parse a [`SequentialSpec`]
+/// to sort any `T: Ord`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Spec {
+    /// `standard`
+    Standard(StandardSorter),
+    /// `bubble`
+    Bubble(BubbleSorter),
+    /// `cocktail_shaker`
+    CocktailShaker(CocktailShakerSorter),
+    /// `comb`
+    Comb(CombSorter),
+    /// `gnome`
+    Gnome(GnomeSorter),
+    /// `insertion` or `insertion:method=rotate_stable`
+    InsertionRotateStable(InsertionSorter<RotateStable>),
+    /// `insertion:method=rotate_unstable`
+    InsertionRotateUnstable(InsertionSorter<RotateUnstable>),
+    /// `insertion:method=swap`
+    InsertionSwap(InsertionSorter<Swap>),
+    /// `odd_even` or `odd_even:parallel=off`
+    OddEvenSequential(OddEvenSorter<SequentialPhases>),
+    /// `odd_even:parallel=on`
+    OddEvenParallel(OddEvenSorter<ParallelPhases>),
+    /// `merge` or `merge:parallel=off`
+    MergeSequential(MergeSorter<Sequential>),
+    /// `merge:parallel=on`
+    MergeParallel(MergeSorter<Parallel>),
+    /// `quick` or `quick:parallel=off`
+    QuickSequential(QuickSorter<Sequential>),
+    /// `quick:parallel=on`
+    QuickParallel(QuickSorter<Parallel>),
+    /// `sample(sorter)`
+    Sample(Box<SampleSorter<Spec>>),
+    /// `hybrid(large,small,threshold)`
+    Hybrid(Box<HybridSorter<Spec, Spec>>),
+}
+
+/// Error returned when parsing an invalid [`Spec`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSpecError {
+    /// Byte offset of the error in the spec.
+    pub position: usize,
+    /// What is wrong there.
+    pub message: String,
+}
+
+impl fmt::Display for ParseSpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid sorter spec at byte {}: {}",
+            self.position, self.message
+        )
+    }
+}
+
+impl Error for ParseSpecError {}
+
+const OPTIMIZATIONS: [(&str, Optimization); 3] = [
+    ("new_len", Optimization::NewLen),
+    ("len_minus_one", Optimization::LenMinusOne),
+    ("none", Optimization::None),
+];
+
+impl<T: Ord + Send + Sync> Sorter<T> for Spec {
+    fn sort(self, slice: &mut [T]) {
+        match self {
+            Self::OddEvenParallel(sorter) => sorter.sort(slice),
+            Self::MergeParallel(sorter) => sorter.sort(slice),
+            Self::QuickParallel(sorter) => sorter.sort(slice),
+            Self::Sample(sorter) => (*sorter).sort(slice),
+            Self::Hybrid(sorter) => (*sorter).sort(slice),
+            spec => SequentialSpec(spec).sort(slice),
+        }
+    }
+}
+
+impl SorterMetadata for Spec {
+    fn metadata(&self) -> Metadata {
+        match self {
+            Self::Standard(sorter) => sorter.metadata(),
+            Self::Bubble(sorter) => sorter.metadata(),
+            Self::CocktailShaker(sorter) => sorter.metadata(),
+            Self::Comb(sorter) => sorter.metadata(),
+            Self::Gnome(sorter) => sorter.metadata(),
+            Self::InsertionRotateStable(sorter) => sorter.metadata(),
+            Self::InsertionRotateUnstable(sorter) => sorter.metadata(),
+            Self::InsertionSwap(sorter) => sorter.metadata(),
+            Self::OddEvenSequential(sorter) => sorter.metadata(),
+            Self::OddEvenParallel(sorter) => sorter.metadata(),
+            Self::MergeSequential(sorter) => sorter.metadata(),
+            Self::MergeParallel(sorter) => sorter.metadata(),
+            Self::QuickSequential(sorter) => sorter.metadata(),
+            Self::QuickParallel(sorter) => sorter.metadata(),
+            Self::Sample(sorter) => sorter.metadata(),
+            Self::Hybrid(sorter) => sorter.metadata(),
+        }
+    }
+}
+
+impl FromStr for Spec {
+    type Err = ParseSpecError;
+
+    fn from_str(spec: &str) -> Result<Self, ParseSpecError> {
+        Parser::parse(spec)?.build()
+    }
+}
+
+impl fmt::Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Standard(_) => f.write_str("standard"),
+            Self::Bubble(sorter) => {
+                f.write_str("bubble")?;
+                let optimization = OPTIMIZATIONS
+                    .iter()
+                    .find(|&&(_, optimization)| optimization == sorter.optimization)
+                    .map_or("new_len", |&(name, _)| name);
+                Params::new(f).param(
+                    "optimization",
+                    optimization,
+                    sorter.optimization != Optimization::default(),
+                )
+            }
+            Self::CocktailShaker(_) => f.write_str("cocktail_shaker"),
+            Self::Comb(sorter) => {
+                f.write_str("comb")?;
+                let (numerator, denominator) = sorter.shrink_ratio();
+                let mut params = Params::new(f);
+                params.param(
+                    "shrink",
+                    Ratio(numerator, denominator),
+                    (numerator, denominator) != CombSorter::default().shrink_ratio(),
+                )?;
+                params.param("rule11", "off", !sorter.rule_of_11())?;
+                params.param("finish", "insertion", sorter.finish() == Finish::Insertion)
+            }
+            Self::Gnome(_) => f.write_str("gnome"),
+            Self::InsertionRotateStable(_) => f.write_str("insertion"),
+            Self::InsertionRotateUnstable(_) => f.write_str("insertion:method=rotate_unstable"),
+            Self::InsertionSwap(_) => f.write_str("insertion:method=swap"),
+            Self::OddEvenSequential(_) => f.write_str("odd_even"),
+            Self::OddEvenParallel(_) => f.write_str("odd_even:parallel=on"),
+            Self::MergeSequential(_) => f.write_str("merge"),
+            Self::MergeParallel(sorter) => {
+                f.write_str("merge")?;
+                parallel_params(f, sorter.parallelism)
+            }
+            Self::QuickSequential(_) => f.write_str("quick"),
+            Self::QuickParallel(sorter) => {
+                f.write_str("quick")?;
+                parallel_params(f, sorter.parallelism)
+            }
+            Self::Sample(sorter) => {
+                write!(f, "sample({})", sorter.sorter)?;
+                let mut params = Params::new(f);
+                params.param("threads", sorter.threads, sorter.threads != 0)?;
+                params.param(
+                    "cutoff",
+                    sorter.sequential_cutoff,
+                    sorter.sequential_cutoff != SampleSorter::<Self>::DEFAULT_SEQUENTIAL_CUTOFF,
+                )?;
+                params.param("buckets", sorter.buckets, sorter.buckets != 0)?;
+                params.param(
+                    "oversampling",
+                    sorter.oversampling,
+                    sorter.oversampling != SampleSorter::<Self>::DEFAULT_OVERSAMPLING,
+                )
+            }
+            Self::Hybrid(sorter) => write!(
+                f,
+                "hybrid({},{},{})",
+                sorter.large, sorter.small, sorter.threshold
+            ),
+        }
+    }
+}
+
+/// [`SequentialSpec`]
+///
+/// A [`Spec`] that sorts on the current thread only, so that it sorts any `T: Ord`, including
+/// elements that cannot be sent to other threads. Parsing fails on the specs that may hand
+/// elements to other threads: the `odd_even`, `merge` and `quick` specs with `parallel=on`, and
+/// `sample`.
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use maniac::{Sorter, spec::SequentialSpec};
+///
+/// let spec: SequentialSpec = "hybrid(merge,insertion,16)".parse()?;
+/// let mut items: Vec<Rc<u32>> = (0..100).rev().map(Rc::new).collect();
+/// spec.sort(&mut items);
+/// assert!(items.is_sorted());
+///
+/// assert!("merge:parallel=on".parse::<SequentialSpec>().is_err());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequentialSpec(Spec);
+
+impl From<SequentialSpec> for Spec {
+    fn from(spec: SequentialSpec) -> Self {
+        spec.0
+    }
+}
+
+impl<T: Ord> Sorter<T> for SequentialSpec {
+    fn sort(self, slice: &mut [T]) {
+        match self.0 {
+            Spec::Standard(sorter) => sorter.sort(slice),
+            Spec::Bubble(sorter) => sorter.sort(slice),
+            Spec::CocktailShaker(sorter) => sorter.sort(slice),
+            Spec::Comb(sorter) => sorter.sort(slice),
+            Spec::Gnome(sorter) => sorter.sort(slice),
+            Spec::InsertionRotateStable(sorter) => sorter.sort(slice),
+            Spec::InsertionRotateUnstable(sorter) => sorter.sort(slice),
+            Spec::InsertionSwap(sorter) => sorter.sort(slice),
+            Spec::OddEvenSequential(sorter) => sorter.sort(slice),
+            Spec::MergeSequential(sorter) => sorter.sort(slice),
+            Spec::QuickSequential(sorter) => sorter.sort(slice),
+            Spec::Hybrid(sorter) => {
+                let HybridSorter {
+                    large,
+                    small,
+                    threshold,
+                } = *sorter;
+                HybridSorter {
+                    large: Self(large),
+                    small: Self(small),
+                    threshold,
+                }
+                .sort(slice);
+            }
+            Spec::OddEvenParallel(_)
+            | Spec::MergeParallel(_)
+            | Spec::QuickParallel(_)
+            | Spec::Sample(_) => unreachable!("parsing rejects the parallel specs"),
+        }
+    }
+}
+
+impl SorterMetadata for SequentialSpec {
+    fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+}
+
+impl FromStr for SequentialSpec {
+    type Err = ParseSpecError;
+
+    fn from_str(spec: &str) -> Result<Self, ParseSpecError> {
+        let node = Parser::parse(spec)?;
+        let spec = node.build()?;
+        node.sequential(&spec)?;
+        Ok(Self(spec))
+    }
+}
+
+impl fmt::Display for SequentialSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Writes the parameters of a parallel `merge` or `quick`.
+fn parallel_params(f: &mut fmt::Formatter<'_>, parallelism: Parallel) -> fmt::Result {
+    let mut params = Params::new(f);
+    params.param("parallel", "on", true)?;
+    params.param("threads", parallelism.threads, parallelism.threads != 0)?;
+    params.param(
+        "cutoff",
+        parallelism.sequential_cutoff,
+        parallelism.sequential_cutoff != Parallel::DEFAULT_SEQUENTIAL_CUTOFF,
+    )
+}
+
+/// Writes the parameters of a spec, separated by commas after a colon.
+struct Params<'a, 'f> {
+    f: &'a mut fmt::Formatter<'f>,
+    first: bool,
+}
+
+impl<'a, 'f> Params<'a, 'f> {
+    fn new(f: &'a mut fmt::Formatter<'f>) -> Self {
+        Self { f, first: true }
+    }
+
+    /// Writes `key=value` if `written`, i.e. if the value is not the default one.
+    fn param(&mut self, key: &str, value: impl fmt::Display, written: bool) -> fmt::Result {
+        if !written {
+            return Ok(());
+        }
+        let separator = if self.first { ':' } else { ',' };
+        self.first = false;
+        write!(self.f, "{separator}{key}={value}")
+    }
+}
+
+/// A shrink ratio, printed as an exact decimal if it has one, as `numerator/denominator`
+/// otherwise.
+struct Ratio(u64, u64);
+
+impl fmt::Display for Ratio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(numerator, denominator) = *self;
+        // The ratio has a finite decimal expansion with `digits` digits iff the denominator
+        // divides `10^digits`.
+        let scale = (0..=19)
+            .map(|digits| (digits, 10_u64.pow(digits)))
+            .find(|&(_, power)| power % denominator == 0);
+        let Some((digits, power)) = scale else {
+            return write!(f, "{numerator}/{denominator}");
+        };
+        let scaled = u128::from(numerator) * u128::from(power / denominator);
+        let (integer, fraction) = (scaled / u128::from(power), scaled % u128::from(power));
+        if digits == 0 {
+            write!(f, "{integer}")
+        } else {
+            write!(f, "{integer}.{fraction:0width$}", width = digits as usize)
+        }
+    }
+}
+
+/// A word of a spec: a name, an argument or a parameter key or value.
+#[derive(Debug, Clone, Copy)]
+struct Word<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+/// A parsed but not yet interpreted spec.
+#[derive(Debug)]
+struct Node<'a> {
+    name: Word<'a>,
+    args: Vec<Node<'a>>,
+    params: Vec<(Word<'a>, Word<'a>)>,
+}
+
+/// How deeply specs may nest: deeper input is rejected before it can overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    /// The number of enclosing specs of the node being parsed.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Parses `input` as a whole.
+    fn parse(input: &'a str) -> Result<Node<'a>, ParseSpecError> {
+        let mut parser = Parser {
+            input,
+            position: 0,
+            depth: 0,
+        };
+        let node = parser.node()?;
+        if let Some(next) = parser.peek() {
+            return Err(parser.error(format!("unexpected `{next}`")));
+        }
+        Ok(node)
+    }
+
+    fn error(&self, message: String) -> ParseSpecError {
+        ParseSpecError {
+            position: self.position,
+            message,
+        }
+    }
+
+    /// The next character, after any whitespace.
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        self.input[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.peek() == Some(expected);
+        if found {
+            self.position += expected.len_utf8();
+        }
+        found
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseSpecError> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{expected}`")))
+        }
+    }
+
+    fn word(&mut self, what: &str) -> Result<Word<'a>, ParseSpecError> {
+        self.peek();
+        let rest = &self.input[self.position..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || "(),:=".contains(c))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error(format!("expected {what}")));
+        }
+        let word = Word {
+            text: &rest[..len],
+            position: self.position,
+        };
+        self.position += len;
+        Ok(word)
+    }
+
+    /// Whether the next tokens are `key=`: after a comma, they tell the next parameter apart
+    /// from the next argument of the enclosing spec.
+    fn at_param(&mut self) -> bool {
+        let start = self.position;
+        let at_param = self.word("").is_ok() && self.eat('=');
+        self.position = start;
+        at_param
+    }
+
+    fn node(&mut self) -> Result<Node<'a>, ParseSpecError> {
+        let name = self.word("a sorter name")?;
+        let mut args = Vec::new();
+        if self.eat('(') {
+            if self.depth == MAX_DEPTH {
+                return Err(self.error(format!("specs nest deeper than {MAX_DEPTH} levels")));
+            }
+            self.depth += 1;
+            loop {
+                args.push(self.node()?);
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            self.depth -= 1;
+            self.expect(')')?;
+        }
+        let mut params = Vec::new();
+        if self.eat(':') {
+            loop {
+                let key = self.word("a parameter name")?;
+                self.expect('=')?;
+                params.push((key, self.word("a parameter value")?));
+                let end = self.position;
+                if !(self.eat(',') && self.at_param()) {
+                    self.position = end;
+                    break;
+                }
+            }
+        }
+        Ok(Node { name, args, params })
+    }
+}
+
+impl Word<'_> {
+    fn error(self, message: String) -> ParseSpecError {
+        ParseSpecError {
+            position: self.position,
+            message,
+        }
+    }
+
+    fn choice<V: Clone>(self, choices: &[(&str, V)]) -> Result<V, ParseSpecError> {
+        choices
+            .iter()
+            .find(|(name, _)| *name == self.text)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| {
+                let names: Vec<String> = choices
+                    .iter()
+                    .map(|(name, _)| format!("`{name}`"))
+                    .collect();
+                self.error(format!(
+                    "expected one of {}, found `{}`",
+                    names.join(", "),
+                    self.text
+                ))
+            })
+    }
+
+    fn switch(self) -> Result<bool, ParseSpecError> {
+        self.choice(&[("on", true), ("off", false)])
+    }
+
+    fn count(self) -> Result<usize, ParseSpecError> {
+        self.text
+            .parse()
+            .map_err(|_| self.error(format!("expected an integer, found `{}`", self.text)))
+    }
+
+    /// Parses `numerator/denominator` or an exact decimal such as `1.25`.
+    fn ratio(self) -> Result<(u64, u64), ParseSpecError> {
+        let invalid = || {
+            self.error(format!(
+                "expected a decimal or a ratio such as `4/3`, found `{}`",
+                self.text
+            ))
+        };
+        let digits = |text: &str| {
+            text.bytes()
+                .all(|byte| byte.is_ascii_digit())
+                .then(|| text.parse::<u64>().ok())
+                .flatten()
+        };
+        if let Some((numerator, denominator)) = self.text.split_once('/') {
+            return digits(numerator)
+                .zip(digits(denominator))
+                .ok_or_else(invalid);
+        }
+        let (integer, fraction) = self.text.split_once('.').unwrap_or((self.text, ""));
+        let power = u32::try_from(fraction.len())
+            .ok()
+            .and_then(|len| 10_u64.checked_pow(len))
+            .ok_or_else(invalid)?;
+        let fraction = if fraction.is_empty() {
+            Some(0)
+        } else {
+            digits(fraction)
+        };
+        digits(integer)
+            .zip(fraction)
+            .and_then(|(integer, fraction)| integer.checked_mul(power)?.checked_add(fraction))
+            .map(|numerator| (numerator, power))
+            .ok_or_else(invalid)
+    }
+}
+
+impl Node<'_> {
+    fn args<const N: usize>(&self) -> Result<&[Node<'_>; N], ParseSpecError> {
+        self.args.as_slice().try_into().map_err(|_| {
+            self.name.error(format!(
+                "`{}` takes {N} argument{}, found {}",
+                self.name.text,
+                if N == 1 { "" } else { "s" },
+                self.args.len()
+            ))
+        })
+    }
+
+    fn unknown(&self, key: Word<'_>) -> ParseSpecError {
+        key.error(format!(
+            "unknown parameter `{}` of `{}`",
+            key.text, self.name.text
+        ))
+    }
+
+    fn build(&self) -> Result<Spec, ParseSpecError> {
+        for (index, (key, _)) in self.params.iter().enumerate() {
+            if self.params[..index]
+                .iter()
+                .any(|(other, _)| other.text == key.text)
+            {
+                return Err(key.error(format!("duplicate parameter `{}`", key.text)));
+            }
+        }
+        if !matches!(self.name.text, "sample" | "hybrid") {
+            self.args::<0>()?;
+        }
+
+        let spec = match self.name.text {
+            "standard" => self.simple(Spec::Standard(StandardSorter))?,
+            "cocktail_shaker" => self.simple(Spec::CocktailShaker(CocktailShakerSorter))?,
+            "gnome" => self.simple(Spec::Gnome(GnomeSorter))?,
+            "bubble" => {
+                let mut sorter = BubbleSorter::default();
+                for &(key, value) in &self.params {
+                    match key.text {
+                        "optimization" => sorter.optimization = value.choice(&OPTIMIZATIONS)?,
+                        _ => return Err(self.unknown(key)),
+                    }
+                }
+                Spec::Bubble(sorter)
+            }
+            "comb" => self.comb()?,
+            "insertion" => self.insertion()?,
+            "odd_even" => {
+                let mut parallel = false;
+                for &(key, value) in &self.params {
+                    match key.text {
+                        "parallel" => parallel = value.switch()?,
+                        _ => return Err(self.unknown(key)),
+                    }
+                }
+                if parallel {
+                    Spec::OddEvenParallel(OddEvenSorter {
+                        phases: ParallelPhases,
+                    })
+                } else {
+                    Spec::OddEvenSequential(OddEvenSorter::default())
+                }
+            }
+            "merge" => match self.parallelism()? {
+                Some(parallelism) => Spec::MergeParallel(MergeSorter { parallelism }),
+                None => Spec::MergeSequential(MergeSorter::default()),
+            },
+            "quick" => match self.parallelism()? {
+                Some(parallelism) => Spec::QuickParallel(QuickSorter { parallelism }),
+                None => Spec::QuickSequential(QuickSorter::default()),
+            },
+            "sample" => self.sample()?,
+            "hybrid" => {
+                let [large, small, threshold] = self.args()?;
+                if let Some(&(key, _)) = self.params.first() {
+                    return Err(self.unknown(key));
+                }
+                Spec::Hybrid(Box::new(HybridSorter {
+                    large: large.build()?,
+                    small: small.build()?,
+                    threshold: threshold.threshold()?,
+                }))
+            }
+            name => {
+                return Err(self.name.error(format!("unknown sorter `{name}`")));
+            }
+        };
+        Ok(spec)
+    }
+
+    /// Returns `spec`, which has no parameters.
+    fn simple(&self, spec: Spec) -> Result<Spec, ParseSpecError> {
+        match self.params.first() {
+            Some(&(key, _)) => Err(self.unknown(key)),
+            None => Ok(spec),
+        }
+    }
+
+    fn insertion(&self) -> Result<Spec, ParseSpecError> {
+        let mut spec = Spec::InsertionRotateStable(InsertionSorter::default());
+        for &(key, value) in &self.params {
+            match key.text {
+                "method" => {
+                    spec = value.choice(&[
+                        (
+                            "rotate_stable",
+                            Spec::InsertionRotateStable(InsertionSorter::default()),
+                        ),
+                        (
+                            "rotate_unstable",
+                            Spec::InsertionRotateUnstable(InsertionSorter {
+                                method: RotateUnstable,
+                            }),
+                        ),
+                        (
+                            "swap",
+                            Spec::InsertionSwap(InsertionSorter { method: Swap }),
+                        ),
+                    ])?;
+                }
+                _ => return Err(self.unknown(key)),
+            }
+        }
+        Ok(spec)
+    }
+
+    fn comb(&self) -> Result<Spec, ParseSpecError> {
+        let mut sorter = CombSorter::default();
+        for &(key, value) in &self.params {
+            match key.text {
+                "shrink" => {
+                    let (numerator, denominator) = value.ratio()?;
+                    sorter = sorter
+                        .with_shrink_ratio(numerator, denominator)
+                        .map_err(|error| value.error(error.to_string()))?;
+                }
+                "rule11" => sorter = sorter.with_rule_of_11(value.switch()?),
+                "finish" => {
+                    sorter =
+                        sorter.with_finish(value.choice(&[
+                            ("bubble", Finish::Bubble),
+                            ("insertion", Finish::Insertion),
+                        ])?);
+                }
+                _ => return Err(self.unknown(key)),
+            }
+        }
+        Ok(Spec::Comb(sorter))
+    }
+
+    /// Interprets the parameters of `merge` and `quick`, which are sequential unless
+    /// `parallel=on`.
+    fn parallelism(&self) -> Result<Option<Parallel>, ParseSpecError> {
+        let mut parallel = false;
+        let mut parallelism = Parallel::default();
+        let mut parallel_only = None;
+        for &(key, value) in &self.params {
+            match key.text {
+                "parallel" => parallel = value.switch()?,
+                "threads" => parallelism.threads = value.count()?,
+                "cutoff" => parallelism.sequential_cutoff = value.count()?,
+                _ => return Err(self.unknown(key)),
+            }
+            if key.text != "parallel" {
+                parallel_only.get_or_insert(key);
+            }
+        }
+        match parallel_only {
+            Some(key) if !parallel => Err(key.error(format!(
+                "`{}` of `{}` needs `parallel=on`",
+                key.text, self.name.text
+            ))),
+            _ => Ok(parallel.then_some(parallelism)),
+        }
+    }
+
+    /// Checks that `spec`, built from the node, sorts on the current thread only.
+    fn sequential(&self, spec: &Spec) -> Result<(), ParseSpecError> {
+        match spec {
+            Spec::OddEvenParallel(_)
+            | Spec::MergeParallel(_)
+            | Spec::QuickParallel(_)
+            | Spec::Sample(_) => Err(self
+                .name
+                .error(format!("`{spec}` hands elements to other threads"))),
+            Spec::Hybrid(sorter) => {
+                let [large, small, _] = self.args()?;
+                large.sequential(&sorter.large)?;
+                small.sequential(&sorter.small)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn sample(&self) -> Result<Spec, ParseSpecError> {
+        let inner = if self.args.is_empty() {
+            Spec::QuickSequential(QuickSorter::default())
+        } else {
+            let [inner] = self.args()?;
+            inner.build()?
+        };
+        let mut sorter = SampleSorter::new(inner);
+        for &(key, value) in &self.params {
+            match key.text {
+                "threads" => sorter.threads = value.count()?,
+                "cutoff" => sorter.sequential_cutoff = value.count()?,
+                "buckets" => sorter.buckets = value.count()?,
+                "oversampling" => sorter.oversampling = value.count()?,
+                _ => return Err(self.unknown(key)),
+            }
+        }
+        Ok(Spec::Sample(Box::new(sorter)))
+    }
+
+    /// Interprets the node as the threshold argument of `hybrid`.
+    fn threshold(&self) -> Result<usize, ParseSpecError> {
+        if self.args.is_empty() && self.params.is_empty() {
+            self.name.count()
+        } else {
+            Err(self.name.error("expected an integer threshold".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorter_tests;
+
+    fn parse(spec: &str) -> Spec {
+        spec.parse()
+            .unwrap_or_else(|error| panic!("{spec}: {error}"))
+    }
+
+    fn error(spec: &str) -> ParseSpecError {
+        spec.parse::<Spec>().unwrap_err()
+    }
+
+    sorter_tests!(
+        spec,
+        parse("hybrid(sample(insertion):cutoff=0,buckets=4,comb,16)")
+    );
+
+    #[test]
+    fn parses_configured_sorters() {
+        assert_eq!(
+            parse("bubble:optimization=len_minus_one"),
+            Spec::Bubble(BubbleSorter {
+                optimization: Optimization::LenMinusOne
+            })
+        );
+        assert_eq!(
+            parse("insertion:method=swap"),
+            Spec::InsertionSwap(InsertionSorter { method: Swap })
+        );
+        assert_eq!(
+            parse("comb:shrink=4/3,finish=insertion"),
+            Spec::Comb(
+                CombSorter::default()
+                    .with_shrink_ratio(4, 3)
+                    .unwrap()
+                    .with_finish(Finish::Insertion)
+            )
+        );
+        assert_eq!(
+            parse("odd_even:parallel=on"),
+            Spec::OddEvenParallel(OddEvenSorter {
+                phases: ParallelPhases
+            })
+        );
+        assert_eq!(
+            parse("hybrid(quick,insertion,16)"),
+            Spec::Hybrid(Box::new(HybridSorter {
+                large: Spec::QuickSequential(QuickSorter::default()),
+                small: Spec::InsertionRotateStable(InsertionSorter::default()),
+                threshold: 16,
+            }))
+        );
+        assert_eq!(
+            parse("sample"),
+            Spec::Sample(Box::new(SampleSorter::new(Spec::QuickSequential(
+                QuickSorter::default()
+            ))))
+        );
+        assert_eq!(
+            parse("merge:parallel=on,threads=4"),
+            Spec::MergeParallel(MergeSorter {
+                parallelism: Parallel {
+                    threads: 4,
+                    ..Parallel::default()
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn prints_canonical_specs_that_round_trip() {
+        for (spec, canonical) in [
+            ("standard", "standard"),
+            ("bubble:optimization=new_len", "bubble"),
+            ("bubble:optimization=none", "bubble:optimization=none"),
+            ("cocktail_shaker", "cocktail_shaker"),
+            ("comb:shrink=1.3,rule11=on", "comb"),
+            ("comb:rule11=off,shrink=1.25", "comb:shrink=1.25,rule11=off"),
+            ("comb:shrink=8/6", "comb:shrink=4/3"),
+            ("comb:shrink=2", "comb:shrink=2"),
+            (
+                "comb:shrink=1.0625,finish=insertion",
+                "comb:shrink=1.0625,finish=insertion",
+            ),
+            (
+                "comb:finish=insertion,rule11=off",
+                "comb:rule11=off,finish=insertion",
+            ),
+            ("gnome", "gnome"),
+            ("insertion:method=rotate_stable", "insertion"),
+            (
+                "insertion:method=rotate_unstable",
+                "insertion:method=rotate_unstable",
+            ),
+            ("odd_even:parallel=off", "odd_even"),
+            ("odd_even:parallel=on", "odd_even:parallel=on"),
+            ("merge:parallel=off", "merge"),
+            (
+                "merge:cutoff=8192,threads=4,parallel=on",
+                "merge:parallel=on,threads=4",
+            ),
+            (
+                "quick:parallel=on,threads=0,cutoff=100",
+                "quick:parallel=on,cutoff=100",
+            ),
+            ("sample", "sample(quick)"),
+            (
+                "sample( quick:parallel=on,threads=2 ) :buckets=8, oversampling=4",
+                "sample(quick:parallel=on,threads=2):buckets=8,oversampling=4",
+            ),
+            ("hybrid(quick,insertion,16)", "hybrid(quick,insertion,16)"),
+            (
+                "hybrid(merge:parallel=on,hybrid(comb:rule11=off,gnome,4),32)",
+                "hybrid(merge:parallel=on,hybrid(comb:rule11=off,gnome,4),32)",
+            ),
+        ] {
+            let parsed = parse(spec);
+            assert_eq!(parsed.to_string(), canonical, "{spec}");
+            assert_eq!(parse(canonical), parsed, "{spec}");
+        }
+    }
+
+    #[test]
+    fn reports_errors_with_their_position() {
+        for (spec, position, message) in [
+            ("", 0, "expected a sorter name"),
+            ("heap", 0, "unknown sorter `heap`"),
+            (
+                "bubble:optimisation=none",
+                7,
+                "unknown parameter `optimisation` of `bubble`",
+            ),
+            (
+                "insertion:method=binary",
+                17,
+                "expected one of `rotate_stable`, `rotate_unstable`, `swap`, found `binary`",
+            ),
+            (
+                "comb:shrink=1",
+                12,
+                "invalid shrink factor 1: it must be greater than 1",
+            ),
+            (
+                "comb:shrink=1.x",
+                12,
+                "expected a decimal or a ratio such as `4/3`, found `1.x`",
+            ),
+            (
+                "comb:rule11=no",
+                12,
+                "expected one of `on`, `off`, found `no`",
+            ),
+            (
+                "comb:shrink=3/0",
+                12,
+                "invalid shrink ratio: its denominator is 0",
+            ),
+            (
+                "comb:rule11=on,rule11=off",
+                15,
+                "duplicate parameter `rule11`",
+            ),
+            (
+                "gnome:parallel=on",
+                6,
+                "unknown parameter `parallel` of `gnome`",
+            ),
+            ("quick:threads=-1", 14, "expected an integer, found `-1`"),
+            (
+                "quick:cutoff=64,threads=2",
+                6,
+                "`cutoff` of `quick` needs `parallel=on`",
+            ),
+            ("quick(insertion)", 0, "`quick` takes 0 arguments, found 1"),
+            (
+                "hybrid(quick,insertion)",
+                0,
+                "`hybrid` takes 3 arguments, found 2",
+            ),
+            (
+                "hybrid(quick,insertion,gnome)",
+                23,
+                "expected an integer, found `gnome`",
+            ),
+            ("hybrid(quick,insertion,16", 25, "expected `)`"),
+            ("quick:threads", 13, "expected `=`"),
+            ("quick gnome", 6, "unexpected `g`"),
+        ] {
+            assert_eq!(
+                error(spec),
+                ParseSpecError {
+                    position,
+                    message: message.to_string()
+                },
+                "{spec}"
+            );
+        }
+        assert_eq!(
+            error("heap").to_string(),
+            "invalid sorter spec at byte 0: unknown sorter `heap`"
+        );
+    }
+
+    #[test]
+    fn rejects_deeply_nested_specs() {
+        let nested = |depth: usize| "sample(".repeat(depth) + "quick" + &")".repeat(depth);
+        assert_eq!(parse(&nested(MAX_DEPTH)).to_string(), nested(MAX_DEPTH));
+        for depth in [MAX_DEPTH + 1, 200_000] {
+            assert_eq!(
+                error(&nested(depth)),
+                ParseSpecError {
+                    position: (MAX_DEPTH + 1) * "sample(".len(),
+                    message: format!("specs nest deeper than {MAX_DEPTH} levels")
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn metadata_follows_the_configuration() {
+        assert!(parse("insertion").metadata().stable);
+        assert!(!parse("insertion:method=rotate_unstable").metadata().stable);
+        assert!(
+            !parse("hybrid(merge,insertion:method=rotate_unstable,8)")
+                .metadata()
+                .stable
+        );
+        assert_eq!(
+            parse("odd_even:parallel=on").metadata().parallel,
+            cfg!(feature = "std")
+        );
+    }
+
+    #[test]
+    fn sequential_specs_sort_any_ord() {
+        use std::rc::Rc;
+
+        for spec in [
+            "insertion",
+            "bubble",
+            "merge",
+            "quick",
+            "odd_even",
+            "hybrid(merge,hybrid(quick,gnome,4),16)",
+        ] {
+            let sorter: SequentialSpec = spec
+                .parse()
+                .unwrap_or_else(|error| panic!("{spec}: {error}"));
+            assert_eq!(sorter.to_string(), spec);
+            let mut items: Vec<Rc<u32>> = (0..100).rev().map(Rc::new).collect();
+            sorter.sort(&mut items);
+            assert!(items.is_sorted(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn sequential_specs_reject_parallel_parts() {
+        for (spec, position, part) in [
+            ("odd_even:parallel=on", 0, "odd_even:parallel=on"),
+            (
+                "quick:parallel=on,threads=1",
+                0,
+                "quick:parallel=on,threads=1",
+            ),
+            ("sample(insertion)", 0, "sample(insertion)"),
+            (
+                "hybrid(gnome,hybrid(merge:parallel=on,gnome,4),16)",
+                20,
+                "merge:parallel=on",
+            ),
+        ] {
+            assert_eq!(
+                spec.parse::<SequentialSpec>().unwrap_err(),
+                ParseSpecError {
+                    position,
+                    message: format!("`{part}` hands elements to other threads"),
+                },
+                "{spec}"
+            );
+            assert!(spec.parse::<Spec>().is_ok(), "{spec}");
+        }
+    }
+}
@@ -1,4 +1,17 @@
-use super::Sorter;
+use super::{Sorter, StableSorter};
+
+/// [`StandardSorter`]
+///
+/// Delegates to the standard library's [`slice::sort`] (a stable driftsort). It is the reference
+/// every other sorter of this crate is compared against.
+///
+/// Unlike the other sorters, it may panic if the [`Ord`] implementation is not a total order.
+///
+/// ### Complexity
+/// | Best | Average | Worst | Space | In-place | Stable |
+/// | ---- | ------- | ----- | ----- | -------- | ------ |
+/// | *O(n)* (already sorted) | *O(n log n)* | *O(n log n)* | *O(n)* | No | Yes |
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct StandardSorter;
 
 impl<T: Ord> Sorter<T> for StandardSorter {
@@ -6,3 +19,13 @@ impl<T: Ord> Sorter<T> for StandardSorter {
         slice.sort();
     }
 }
+
+impl<T: Ord> StableSorter<T> for StandardSorter {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorter_tests;
+
+    sorter_tests!(standard, StandardSorter, may_panic_on_inconsistent_order);
+}
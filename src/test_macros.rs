@@ -1,5 +1,106 @@
 #![cfg(test)]
 
+use std::marker::PhantomData;
+
+use crate::{Sorter, StableSorter};
+
+/// Element whose ordering only looks at `key`, so that `tag` reveals reorderings of equal keys.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Item {
+    pub key: i32,
+    pub tag: usize,
+}
+
+impl Ord for Item {
+    fn cmp(&self, rhs: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&rhs.key)
+    }
+}
+impl PartialOrd for Item {
+    fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+/// Zero-sized element, all of which compare equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Unit;
+
+/// Zero-sized element with an inconsistent order: every element is greater than every other.
+#[derive(Clone, Copy, Debug)]
+pub struct Contrary;
+
+impl PartialEq for Contrary {
+    fn eq(&self, _: &Self) -> bool {
+        false
+    }
+}
+impl Eq for Contrary {}
+impl Ord for Contrary {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Greater
+    }
+}
+impl PartialOrd for Contrary {
+    fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+/// Asserts that `sorter` keeps items with equal keys in their original relative order, whether it
+/// sorts in ascending or in descending order.
+pub fn assert_stable<S: Sorter<Item>>(sorter: S) {
+    let mut data = vec![
+        Item { key: 2, tag: 0 },
+        Item { key: 1, tag: 1 },
+        Item { key: 2, tag: 2 },
+        Item { key: 1, tag: 3 },
+    ];
+
+    let ascending = vec![
+        Item { key: 1, tag: 1 },
+        Item { key: 1, tag: 3 },
+        Item { key: 2, tag: 0 },
+        Item { key: 2, tag: 2 },
+    ];
+    let descending = vec![
+        Item { key: 2, tag: 0 },
+        Item { key: 2, tag: 2 },
+        Item { key: 1, tag: 1 },
+        Item { key: 1, tag: 3 },
+    ];
+
+    sorter.sort(&mut data);
+    assert!(data == ascending || data == descending, "{data:?}");
+}
+
+/// Type of a sorter, to tell at compile time whether it implements [`StableSorter`] with
+/// [`IsStableSorter`] and [`NotStableSorter`]: `(&probe).is_stable_sorter()` resolves to the
+/// former when the bound holds, and falls back to the latter through an extra autoref otherwise.
+pub struct Probe<S>(PhantomData<S>);
+
+impl<S> Probe<S> {
+    pub fn new(_: &S) -> Self {
+        Self(PhantomData)
+    }
+}
+
+pub trait IsStableSorter {
+    fn is_stable_sorter(&self) -> bool {
+        true
+    }
+}
+
+impl<S: StableSorter<Item>> IsStableSorter for Probe<S> {}
+
+pub trait NotStableSorter {
+    fn is_stable_sorter(&self) -> bool {
+        false
+    }
+}
+
+impl<S> NotStableSorter for &Probe<S> {}
+
 /// Runs basic correctness checks on a sorter type.
 ///
 /// ```
@@ -19,7 +120,7 @@ macro_rules! sorter_common_tests {
             use std::fmt::Debug;
             use $crate::Sorter;
 
-            fn run_sort<T: Ord + Debug + Clone>(mut data: Vec<T>) -> Vec<T> {
+            fn run_sort<T: Ord + Debug + Clone + Send + Sync>(mut data: Vec<T>) -> Vec<T> {
                 let mut expected = data.clone();
                 expected.sort();
 
@@ -59,7 +160,8 @@ macro_rules! sorter_common_tests {
 }
 
 /// Runs stability checks on a sorter type.
-/// Invoke this only for algorithms that are supposed to be stable.
+/// Invoke this only for algorithms that are supposed to be stable: the sorter must implement
+/// [`StableSorter`](crate::StableSorter) and declare itself stable in its [`SorterMetadata`](crate::SorterMetadata).
 ///
 /// ```
 /// #[cfg(test)]
@@ -75,45 +177,143 @@ macro_rules! sorter_stability_tests {
     ($mod_name:ident, $ctor:expr) => {
         mod $mod_name {
             use super::*;
-            use $crate::Sorter;
+            use $crate::SorterMetadata;
+
+            #[test]
+            fn preserves_relative_order() {
+                // Only compiles if the sorter is a `StableSorter`.
+                fn stable<S: $crate::StableSorter<$crate::test_macros::Item>>(sorter: S) -> S {
+                    sorter
+                }
+                $crate::test_macros::assert_stable(stable($ctor));
+            }
+
+            #[test]
+            fn metadata_declares_stable() {
+                assert!($ctor.metadata().stable);
+            }
+        }
+    };
+}
+
+/// Runs every check that applies to a sorter type, as declared by its [`SorterMetadata`]:
+/// the common checks and the [`testkit`](crate::testkit) battery always, and the stability checks
+/// only if the sorter claims to be stable. Every sorter must also never panic on an inconsistent
+/// `Ord`, unless `may_panic_on_inconsistent_order` is passed as a third argument.
+///
+/// [`SorterMetadata`]: crate::SorterMetadata
+///
+/// ```
+/// #[cfg(test)]
+/// mod tests {
+///     use super::*;
+///     use crate::sorter_tests;
+///
+///     sorter_tests!(insertion, InsertionSorter::default());
+/// }
+/// ```
+#[macro_export]
+macro_rules! sorter_tests {
+    ($mod_name:ident, $ctor:expr) => {
+        $crate::sorter_tests!(@tests $mod_name, $ctor, {
+            #[test]
+            fn never_panics_on_inconsistent_order() {
+                $crate::testkit::assert_never_panics_on_inconsistent_order(
+                    &$ctor,
+                    &$crate::testkit::Config::default(),
+                );
+            }
 
-            #[derive(Clone, Debug, Eq, PartialEq)]
-            struct Item {
-                key: i32,
-                tag: usize,
+            #[test]
+            fn never_panics_on_inconsistent_zero_sized_elements() {
+                let mut items = vec![$crate::test_macros::Contrary; 300];
+                $ctor.sort(&mut items);
+                assert_eq!(items.len(), 300);
+            }
+        });
+    };
+    // The standard library may panic on an inconsistent `Ord`, which its documentation allows.
+    ($mod_name:ident, $ctor:expr, may_panic_on_inconsistent_order) => {
+        $crate::sorter_tests!(@tests $mod_name, $ctor, {});
+    };
+    (@tests $mod_name:ident, $ctor:expr, { $($extra:tt)* }) => {
+        mod $mod_name {
+            use super::*;
+            use $crate::SorterMetadata;
+
+            $crate::sorter_common_tests!(common, $ctor);
+            $crate::sorter_conformance_tests!(conformance, $ctor);
+
+            /// Whether the sorter declares itself stable, by implementing `StableSorter` or
+            /// through its metadata, which must then agree: a sorter cannot skip the stability
+            /// checks by wrongly declaring itself unstable.
+            fn declared_stable() -> bool {
+                #[allow(unused_imports)]
+                use $crate::test_macros::{IsStableSorter as _, NotStableSorter as _};
+
+                let sorter = $ctor;
+                let implemented = (&$crate::test_macros::Probe::new(&sorter)).is_stable_sorter();
+                assert!(
+                    !implemented || sorter.metadata().stable,
+                    "implements StableSorter but its metadata declares it unstable"
+                );
+                implemented || sorter.metadata().stable
             }
 
-            impl Ord for Item {
-                fn cmp(&self, rhs: &Self) -> std::cmp::Ordering {
-                    self.key.cmp(&rhs.key)
+            #[test]
+            fn zero_sized_elements() {
+                let mut items = vec![$crate::test_macros::Unit; 300];
+                $ctor.sort(&mut items);
+                assert_eq!(items.len(), 300);
+            }
+
+            #[test]
+            fn metadata_agrees_with_stable_sorter() {
+                declared_stable();
+            }
+
+            #[test]
+            fn stable_if_declared() {
+                let sorter = $ctor;
+                if declared_stable() {
+                    $crate::test_macros::assert_stable(sorter);
                 }
             }
-            impl PartialOrd for Item {
-                fn partial_cmp(&self, rhs: &Self) -> Option<std::cmp::Ordering> {
-                    Some(self.cmp(rhs))
+
+            #[test]
+            fn stable_with_duplicates_if_declared() {
+                let sorter = $ctor;
+                if declared_stable() {
+                    $crate::testkit::assert_stable_with_duplicates(
+                        &sorter,
+                        &$crate::testkit::Config::default(),
+                    );
                 }
             }
 
             #[test]
-            fn preserves_relative_order() {
-                let mut data = vec![
-                    Item { key: 2, tag: 0 },
-                    Item { key: 1, tag: 1 },
-                    Item { key: 2, tag: 2 },
-                    Item { key: 1, tag: 3 },
-                ];
-
-                let expected = vec![
-                    Item { key: 1, tag: 1 },
-                    Item { key: 1, tag: 3 },
-                    Item { key: 2, tag: 0 },
-                    Item { key: 2, tag: 2 },
-                ];
+            fn stable_all_sequences_if_declared() {
+                let sorter = $ctor;
+                if declared_stable() {
+                    $crate::testkit::assert_stable_all_sequences(
+                        &sorter,
+                        &$crate::testkit::Config::default(),
+                    );
+                }
+            }
 
+            #[test]
+            fn stable_property_if_declared() {
                 let sorter = $ctor;
-                sorter.sort(&mut data);
-                assert_eq!(data, expected);
+                if declared_stable() {
+                    $crate::testkit::assert_stable_property(
+                        &sorter,
+                        &$crate::testkit::Config::default(),
+                    );
+                }
             }
+
+            $($extra)*
         }
     };
 }
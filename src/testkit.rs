@@ -0,0 +1,438 @@
+use std::{
+    cmp::Ordering,
+    fmt::Debug,
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
+
+use crate::{
+    Sorter,
+    dataset::{Distribution, Rng},
+};
+
+/// Exhaustive checks over every permutation of small inputs.
+pub mod exhaustive;
+pub use exhaustive::{assert_sorts_all_permutations, assert_stable_all_sequences};
+/// Minimal property-based testing engine with shrinking of failing inputs.
+pub mod property;
+pub use property::{assert_sorted_permutation_property, assert_stable_property};
+/// Panic safety checks with comparisons that panic at every possible point.
+pub mod panic_safety;
+pub use panic_safety::assert_panic_safe;
+/// Termination checks with comparators that break the total order laws.
+pub mod inconsistent;
+pub use inconsistent::{
+    assert_never_panics_on_inconsistent_order, assert_terminates_on_inconsistent_order,
+};
+
+/// Sizes of the inputs generated by [`Config`], used by most checks of the kit.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Seed of every generated input, so that failures can be reproduced.
+    pub seed: u64,
+    /// Lengths tried for every [`Distribution`].
+    pub lengths: Vec<usize>,
+    /// Length of the large random inputs.
+    pub large_len: usize,
+    /// Length of the zero-sized-type inputs, which cost no memory.
+    pub huge_len: usize,
+    /// Largest length for which every permutation is tried. There are `n!` of them, so `9` is
+    /// about the practical limit.
+    pub exhaustive_len: usize,
+    /// Number of distinct keys of the exhaustive stability checks, which try `keys^n` sequences.
+    pub exhaustive_keys: usize,
+    /// Number of random inputs tried by each [`property`] check.
+    pub property_cases: usize,
+    /// Largest length of the random inputs of the [`property`] checks.
+    pub property_max_len: usize,
+    /// Length of the inputs of the [`panic_safety`] checks, which sort each input once per
+    /// comparison it needs.
+    pub panic_len: usize,
+    /// Largest of the [`lengths`](Config::lengths) tried by the [`inconsistent`] checks.
+    pub inconsistent_max_len: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            seed: 0x6D61_6E69_6163,
+            lengths: vec![0, 1, 2, 3, 4, 5, 7, 8, 16, 31, 64, 100, 257],
+            large_len: 2_000,
+            huge_len: 1 << 20,
+            exhaustive_len: 8,
+            exhaustive_keys: 3,
+            property_cases: 256,
+            property_max_len: 64,
+            panic_len: 32,
+            inconsistent_max_len: 100,
+        }
+    }
+}
+
+/// Element ordered by `key` only, so that `tag` reveals the reordering of equal keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tagged {
+    /// The sorting key.
+    pub key: i64,
+    /// Position of the element in the original input.
+    pub tag: usize,
+}
+
+impl Ord for Tagged {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl PartialOrd for Tagged {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Heap-allocated element counting how many times it has been dropped, used to detect leaked
+/// or duplicated elements.
+#[derive(Debug)]
+pub struct DropCounter<'a> {
+    value: Box<i64>,
+    drops: &'a AtomicUsize,
+}
+
+impl<'a> DropCounter<'a> {
+    /// Creates an element holding `value` that increments `drops` when dropped.
+    pub fn new(value: i64, drops: &'a AtomicUsize) -> Self {
+        Self {
+            value: Box::new(value),
+            drops,
+        }
+    }
+
+    /// Returns the value held by the element.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        *self.value
+    }
+}
+
+impl Drop for DropCounter<'_> {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, AtomicOrdering::Relaxed);
+    }
+}
+
+impl PartialEq for DropCounter<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl Eq for DropCounter<'_> {}
+
+impl Ord for DropCounter<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl PartialOrd for DropCounter<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Sorts a copy of `input` with `sorter` and compares it against the standard library's stable
+/// sort, which also checks stability when `T` has elements that are equal but distinguishable.
+fn assert_matches_std<S, T>(sorter: &S, input: &[T], context: &dyn Debug)
+where
+    S: Sorter<T> + Clone,
+    T: Ord + Clone + Debug,
+{
+    let mut expected = input.to_vec();
+    expected.sort();
+
+    let mut actual = input.to_vec();
+    sorter.clone().sort(&mut actual);
+    assert!(
+        actual == expected,
+        "{context:?}\n  input:    {input:?}\n  expected: {expected:?}\n  actual:   {actual:?}"
+    );
+}
+
+/// Checks that `sorter` sorts every [`Distribution`] at every length of the [`Config`].
+///
+/// # Panics
+///
+/// Panics with the failing input if the output differs from the standard library's.
+pub fn assert_sorts_all_shapes<S: Sorter<i64> + Clone>(sorter: &S, config: &Config) {
+    let mut rng = Rng::new(config.seed);
+    for distribution in Distribution::ALL {
+        for &len in &config.lengths {
+            let input = distribution.generate(len, &mut rng);
+            assert_matches_std(sorter, &input, &(distribution, len));
+        }
+    }
+}
+
+/// Checks that `sorter` sorts a random input of [`Config::large_len`] elements.
+///
+/// # Panics
+///
+/// Panics if the output differs from the standard library's.
+pub fn assert_sorts_large_random<S: Sorter<i64> + Clone>(sorter: &S, config: &Config) {
+    let mut rng = Rng::new(config.seed);
+    let input = Distribution::Random.generate(config.large_len, &mut rng);
+
+    let mut expected = input.clone();
+    expected.sort_unstable();
+    let mut actual = input;
+    sorter.clone().sort(&mut actual);
+    assert!(actual == expected, "large random input is not sorted");
+}
+
+/// Checks that `sorter` keeps equal keys in their original order, on every [`Distribution`] with
+/// only a handful of distinct keys, up to [`Config::large_len`] elements.
+///
+/// # Panics
+///
+/// Panics with the failing input if equal keys are reordered.
+pub fn assert_stable_with_duplicates<S: Sorter<Tagged> + Clone>(sorter: &S, config: &Config) {
+    let mut rng = Rng::new(config.seed);
+    let lengths = config.lengths.iter().copied().chain([config.large_len]);
+    for len in lengths {
+        for distribution in Distribution::ALL {
+            let input: Vec<Tagged> = distribution
+                .generate(len, &mut rng)
+                .into_iter()
+                .enumerate()
+                .map(|(tag, key)| Tagged { key: key % 4, tag })
+                .collect();
+            assert_matches_std(sorter, &input, &(distribution, len));
+        }
+    }
+}
+
+/// Checks that `sorter` handles zero-sized elements, up to [`Config::huge_len`] of them.
+///
+/// # Panics
+///
+/// Panics if the slice length changes, which would reveal out-of-bounds bookkeeping.
+pub fn assert_sorts_zero_sized<S: Sorter<()> + Clone>(sorter: &S, config: &Config) {
+    for len in config.lengths.iter().copied().chain([config.huge_len]) {
+        let mut units = vec![(); len];
+        sorter.clone().sort(&mut units);
+        assert_eq!(units.len(), len);
+    }
+}
+
+/// Checks that `sorter` moves elements with a [`Drop`] implementation without dropping,
+/// duplicating or leaking any of them.
+///
+/// # Panics
+///
+/// Panics if an element is dropped during the sort, if the output is not sorted, or if the number
+/// of drops afterwards differs from the number of elements.
+pub fn assert_drops_each_element_once<S>(sorter: &S, config: &Config)
+where
+    S: for<'a> Sorter<DropCounter<'a>> + Clone,
+{
+    let mut rng = Rng::new(config.seed);
+    for distribution in Distribution::ALL {
+        for &len in &config.lengths {
+            let drops = AtomicUsize::new(0);
+            let input = distribution.generate(len, &mut rng);
+
+            let mut elements: Vec<DropCounter<'_>> = input
+                .iter()
+                .map(|&value| DropCounter::new(value, &drops))
+                .collect();
+            sorter.clone().sort(&mut elements);
+            assert_eq!(
+                drops.load(AtomicOrdering::Relaxed),
+                0,
+                "{distribution:?} {len}"
+            );
+
+            let mut expected = input;
+            expected.sort_unstable();
+            let actual: Vec<i64> = elements.iter().map(DropCounter::value).collect();
+            assert_eq!(actual, expected, "{distribution:?} {len}");
+
+            drop(elements);
+            assert_eq!(
+                drops.load(AtomicOrdering::Relaxed),
+                len,
+                "{distribution:?} {len}"
+            );
+        }
+    }
+}
+
+/// Generates the whole conformance battery of the [`testkit`](crate::testkit) for a sorter.
+///
+/// The first argument names the generated test module, the second builds the sorter. Add `stable`
+/// as a third argument to also check stability. The sorter must implement [`Sorter`] for every
+/// `T: Ord` (at least `i64`, `usize`, `()`, [`Tagged`], [`DropCounter`],
+/// [`Bomb`](panic_safety::Bomb) and [`Chaos`](inconsistent::Chaos)).
+///
+/// ```
+/// use maniac::Sorter;
+///
+/// #[derive(Clone)]
+/// struct MySorter;
+///
+/// impl<T: Ord> Sorter<T> for MySorter {
+///     fn sort(self, slice: &mut [T]) {
+///         slice.sort();
+///     }
+/// }
+///
+/// maniac::sorter_conformance_tests!(my_sorter, MySorter, stable);
+/// ```
+#[macro_export]
+macro_rules! sorter_conformance_tests {
+    ($mod_name:ident, $ctor:expr) => {
+        $crate::sorter_conformance_tests!(@tests $mod_name, $ctor, {});
+    };
+    ($mod_name:ident, $ctor:expr, stable) => {
+        $crate::sorter_conformance_tests!(@tests $mod_name, $ctor, {
+            #[test]
+            fn stable_with_duplicates() {
+                $crate::testkit::assert_stable_with_duplicates(&$ctor, &Config::default());
+            }
+            #[test]
+            fn stable_all_sequences() {
+                $crate::testkit::assert_stable_all_sequences(&$ctor, &Config::default());
+            }
+            #[test]
+            fn stable_property() {
+                $crate::testkit::assert_stable_property(&$ctor, &Config::default());
+            }
+        });
+    };
+    (@tests $mod_name:ident, $ctor:expr, { $($extra:tt)* }) => {
+        #[cfg(test)]
+        mod $mod_name {
+            use super::*;
+            use $crate::testkit::Config;
+
+            #[test]
+            fn all_shapes() {
+                $crate::testkit::assert_sorts_all_shapes(&$ctor, &Config::default());
+            }
+            #[test]
+            fn large_random() {
+                $crate::testkit::assert_sorts_large_random(&$ctor, &Config::default());
+            }
+            #[test]
+            fn zero_sized() {
+                $crate::testkit::assert_sorts_zero_sized(&$ctor, &Config::default());
+            }
+            #[test]
+            fn drops_each_element_once() {
+                $crate::testkit::assert_drops_each_element_once(&$ctor, &Config::default());
+            }
+            #[test]
+            fn all_permutations() {
+                $crate::testkit::assert_sorts_all_permutations(&$ctor, &Config::default());
+            }
+            #[test]
+            fn sorted_permutation_property() {
+                $crate::testkit::assert_sorted_permutation_property(&$ctor, &Config::default());
+            }
+            #[test]
+            fn panic_safe() {
+                $crate::testkit::assert_panic_safe(&$ctor, &Config::default());
+            }
+            #[test]
+            fn terminates_on_inconsistent_order() {
+                $crate::testkit::assert_terminates_on_inconsistent_order(
+                    &$ctor,
+                    &Config::default(),
+                );
+            }
+
+            $($extra)*
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::StandardSorter;
+
+    #[cfg(feature = "alloc")]
+    sorter_conformance_tests!(standard, StandardSorter, stable);
+
+    #[derive(Clone)]
+    struct Broken;
+
+    impl<T: Ord> Sorter<T> for Broken {
+        fn sort(self, slice: &mut [T]) {
+            slice.sort();
+            if slice.len() > 2 {
+                slice.swap(0, 1);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected")]
+    fn detects_unsorted_output() {
+        assert_sorts_all_shapes(&Broken, &Config::default());
+    }
+
+    /// Sorts correctly except for one specific permutation.
+    #[derive(Clone)]
+    struct BrokenOnce;
+
+    impl<T: Ord> Sorter<T> for BrokenOnce {
+        fn sort(self, slice: &mut [T]) {
+            let skip = slice.len() == 5 && slice.is_sorted_by(|a, b| a > b);
+            if !skip {
+                slice.sort();
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "permutation [4, 3, 2, 1, 0]")]
+    fn exhaustive_finds_single_failing_permutation() {
+        assert_sorts_all_permutations(&BrokenOnce, &Config::default());
+    }
+
+    #[derive(Clone)]
+    struct Unstable;
+
+    impl<T: Ord> Sorter<T> for Unstable {
+        fn sort(self, slice: &mut [T]) {
+            slice.sort_unstable();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected")]
+    fn detects_instability() {
+        assert_stable_with_duplicates(&Unstable, &Config::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "sequence")]
+    fn exhaustive_detects_instability() {
+        // `slice::sort_unstable` uses a stable insertion sort on tiny inputs, so reverse the
+        // runs of equal keys to make sure the instability is visible.
+        #[derive(Clone)]
+        struct ReverseEqual;
+
+        impl<T: Ord> Sorter<T> for ReverseEqual {
+            fn sort(self, slice: &mut [T]) {
+                slice.sort();
+                for run in slice.chunk_by_mut(|a, b| a.cmp(b).is_eq()) {
+                    run.reverse();
+                }
+            }
+        }
+
+        assert_stable_all_sequences(&ReverseEqual, &Config::default());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::Sorter;
+
+use super::{Config, Tagged};
+
+/// Calls `visit` with every permutation of `0..len` (Heap's algorithm, iterative form).
+pub fn for_each_permutation(len: usize, mut visit: impl FnMut(&[usize])) {
+    let mut items: Vec<usize> = (0..len).collect();
+    let mut counters = vec![0; len];
+    visit(&items);
+
+    let mut i = 1;
+    while i < len {
+        if counters[i] < i {
+            let other = if i % 2 == 0 { 0 } else { counters[i] };
+            items.swap(other, i);
+            visit(&items);
+            counters[i] += 1;
+            i = 1;
+        } else {
+            counters[i] = 0;
+            i += 1;
+        }
+    }
+}
+
+/// Calls `visit` with every sequence of `len` keys drawn from `0..keys`, i.e. every permutation of
+/// every multiset of `len` elements with at most `keys` distinct values.
+pub fn for_each_sequence(len: usize, keys: usize, mut visit: impl FnMut(&[usize])) {
+    if len > 0 && keys == 0 {
+        return;
+    }
+    let mut digits = vec![0; len];
+    loop {
+        visit(&digits);
+
+        // Odometer increment, least significant digit last.
+        let Some(position) = digits.iter().rposition(|&digit| digit + 1 < keys) else {
+            return;
+        };
+        digits[position] += 1;
+        digits[position + 1..].fill(0);
+    }
+}
+
+/// Checks that `sorter` sorts every permutation of `0..n`, for every `n` up to
+/// [`Config::exhaustive_len`].
+///
+/// # Panics
+///
+/// Panics with the first permutation that is not sorted correctly.
+pub fn assert_sorts_all_permutations<S: Sorter<usize> + Clone>(sorter: &S, config: &Config) {
+    for len in 0..=config.exhaustive_len {
+        let expected: Vec<usize> = (0..len).collect();
+        let mut buffer = Vec::with_capacity(len);
+        for_each_permutation(len, |permutation| {
+            buffer.clear();
+            buffer.extend_from_slice(permutation);
+            sorter.clone().sort(&mut buffer);
+            assert!(
+                buffer == expected,
+                "permutation {permutation:?} sorted as {buffer:?}"
+            );
+        });
+    }
+}
+
+/// Checks that `sorter` sorts every sequence of up to [`Config::exhaustive_len`] elements drawn
+/// from [`Config::exhaustive_keys`] distinct keys, keeping equal keys in their original order.
+///
+/// # Panics
+///
+/// Panics with the first sequence that is not sorted stably.
+pub fn assert_stable_all_sequences<S: Sorter<Tagged> + Clone>(sorter: &S, config: &Config) {
+    for len in 0..=config.exhaustive_len {
+        let mut buffer = Vec::with_capacity(len);
+        let mut expected = Vec::with_capacity(len);
+        for_each_sequence(len, config.exhaustive_keys, |keys| {
+            buffer.clear();
+            buffer.extend(keys.iter().enumerate().map(|(tag, &key)| Tagged {
+                key: i64::try_from(key).expect("key count fits in an i64"),
+                tag,
+            }));
+            expected.clear();
+            expected.extend_from_slice(&buffer);
+            expected.sort();
+
+            sorter.clone().sort(&mut buffer);
+            assert!(
+                buffer == expected,
+                "sequence {keys:?} sorted as {:?}",
+                buffer
+                    .iter()
+                    .map(|item| (item.key, item.tag))
+                    .collect::<Vec<_>>()
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation_count() {
+        for (len, count) in [(0, 1), (1, 1), (3, 6), (5, 120)] {
+            let mut seen = Vec::new();
+            for_each_permutation(len, |permutation| seen.push(permutation.to_vec()));
+            assert_eq!(seen.len(), count);
+            seen.sort();
+            seen.dedup();
+            assert_eq!(seen.len(), count, "duplicate permutations");
+        }
+    }
+
+    #[test]
+    fn sequence_count() {
+        for (len, keys, count) in [(0, 3, 1), (1, 3, 3), (4, 2, 16), (3, 3, 27), (2, 0, 0)] {
+            let mut total = 0;
+            for_each_sequence(len, keys, |_| total += 1);
+            assert_eq!(total, count);
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    cmp::Ordering,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+    },
+};
+
+use crate::{
+    Sorter,
+    dataset::{Distribution, Rng},
+};
+
+use super::Config;
+
+/// Way in which the [`Ord`] implementation of a [`Chaos`] element breaks the total order laws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// Every comparison returns a random ordering.
+    Random,
+    /// Every element is less than every other one, itself included.
+    AlwaysLess,
+    /// Every element is greater than every other one, itself included.
+    AlwaysGreater,
+    /// Rock-paper-scissors on the values modulo 3, which is not transitive.
+    Cyclic,
+    /// Every other comparison returns the reversed ordering.
+    Flipping,
+}
+
+impl Inconsistency {
+    /// Every inconsistency, in declaration order.
+    pub const ALL: [Self; 5] = [
+        Self::Random,
+        Self::AlwaysLess,
+        Self::AlwaysGreater,
+        Self::Cyclic,
+        Self::Flipping,
+    ];
+}
+
+/// Shared state of the [`Chaos`] elements of one input: decides every comparison and panics once
+/// a comparison budget is exhausted, so that a sorter looping forever fails instead of hanging.
+#[derive(Debug)]
+pub struct Oracle {
+    inconsistency: Inconsistency,
+    rng: Mutex<Rng>,
+    comparisons: AtomicUsize,
+    budget: usize,
+}
+
+impl Oracle {
+    /// Creates an oracle answering with `inconsistency` for at most `budget` comparisons.
+    #[must_use]
+    pub fn new(inconsistency: Inconsistency, seed: u64, budget: usize) -> Self {
+        Self {
+            inconsistency,
+            rng: Mutex::new(Rng::new(seed)),
+            comparisons: AtomicUsize::new(0),
+            budget,
+        }
+    }
+
+    /// Number of comparisons made so far.
+    pub fn comparisons(&self) -> usize {
+        self.comparisons.load(AtomicOrdering::Relaxed)
+    }
+
+    fn compare(&self, a: i64, b: i64) -> Ordering {
+        let comparison = self.comparisons.fetch_add(1, AtomicOrdering::Relaxed);
+        assert!(
+            comparison < self.budget,
+            "comparison budget of {} exhausted",
+            self.budget
+        );
+        match self.inconsistency {
+            Inconsistency::Random => {
+                let mut rng = self.rng.lock().unwrap_or_else(PoisonError::into_inner);
+                [Ordering::Less, Ordering::Equal, Ordering::Greater][rng.below(3)]
+            }
+            Inconsistency::AlwaysLess => Ordering::Less,
+            Inconsistency::AlwaysGreater => Ordering::Greater,
+            Inconsistency::Cyclic => match (b.rem_euclid(3) - a.rem_euclid(3)).rem_euclid(3) {
+                0 => Ordering::Equal,
+                1 => Ordering::Less,
+                _ => Ordering::Greater,
+            },
+            Inconsistency::Flipping if comparison % 2 == 1 => b.cmp(&a),
+            Inconsistency::Flipping => a.cmp(&b),
+        }
+    }
+}
+
+/// Element whose [`Ord`] implementation is inconsistent, as decided by its [`Oracle`].
+#[derive(Debug)]
+pub struct Chaos<'a> {
+    value: i64,
+    oracle: &'a Oracle,
+}
+
+impl<'a> Chaos<'a> {
+    /// Creates an element holding `value` and compared by `oracle`.
+    pub fn new(value: i64, oracle: &'a Oracle) -> Self {
+        Self { value, oracle }
+    }
+
+    /// Returns the value held by the element.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        self.value
+    }
+}
+
+impl Ord for Chaos<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.oracle.compare(self.value, other.value)
+    }
+}
+
+impl PartialOrd for Chaos<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Chaos<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Chaos<'_> {}
+
+/// Comparisons allowed to sort `len` elements with an inconsistent order: enough for any
+/// quadratic sorter of this crate, small enough to catch an endless loop quickly.
+#[must_use]
+pub fn comparison_budget(len: usize) -> usize {
+    4 * len * len + 16
+}
+
+/// Sorts `input` with `inconsistency`, and checks that the sorter terminates within the
+/// [`comparison_budget`] and leaves the slice a permutation of `input`. Returns the message of the
+/// panic of the sorter, if any.
+fn sort_inconsistently<S>(
+    sorter: &S,
+    input: &[i64],
+    inconsistency: Inconsistency,
+    seed: u64,
+) -> Option<String>
+where
+    S: for<'a> Sorter<Chaos<'a>> + Clone,
+{
+    let budget = comparison_budget(input.len());
+    let oracle = Oracle::new(inconsistency, seed, budget);
+    let mut elements: Vec<Chaos<'_>> = input
+        .iter()
+        .map(|&value| Chaos::new(value, &oracle))
+        .collect();
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| sorter.clone().sort(&mut elements)));
+    assert!(
+        oracle.comparisons() <= budget,
+        "{inconsistency:?} order on {input:?}: no termination within {budget} comparisons"
+    );
+
+    let mut values: Vec<i64> = elements.iter().map(Chaos::value).collect();
+    values.sort_unstable();
+    let mut expected = input.to_vec();
+    expected.sort_unstable();
+    assert!(
+        values == expected,
+        "{inconsistency:?} order left {input:?} as a non-permutation {:?}",
+        elements.iter().map(Chaos::value).collect::<Vec<_>>()
+    );
+
+    outcome.err().map(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default()
+    })
+}
+
+/// Runs `check` on every [`Inconsistency`] for every [`Distribution`] at every length of the
+/// [`Config`] up to [`Config::inconsistent_max_len`].
+fn for_each_inconsistent_input(config: &Config, mut check: impl FnMut(&[i64], Inconsistency, u64)) {
+    let mut rng = Rng::new(config.seed);
+    let lengths = config.lengths.iter().copied();
+    for len in lengths.filter(|&len| len <= config.inconsistent_max_len) {
+        for distribution in Distribution::ALL {
+            let input = distribution.generate(len, &mut rng);
+            for inconsistency in Inconsistency::ALL {
+                check(&input, inconsistency, rng.next_u64());
+            }
+        }
+    }
+}
+
+/// Checks that `sorter` copes with an inconsistent [`Ord`]: it must terminate within the
+/// [`comparison_budget`] and leave the slice a permutation of the original, in any order.
+///
+/// Panicking is allowed, as the standard library may do when it detects an inconsistent order,
+/// see [`assert_never_panics_on_inconsistent_order`] for the stricter check.
+///
+/// # Panics
+///
+/// Panics if the sorter does not terminate in time, or loses or duplicates an element.
+pub fn assert_terminates_on_inconsistent_order<S>(sorter: &S, config: &Config)
+where
+    S: for<'a> Sorter<Chaos<'a>> + Clone,
+{
+    for_each_inconsistent_input(config, |input, inconsistency, seed| {
+        sort_inconsistently(sorter, input, inconsistency, seed);
+    });
+}
+
+/// Like [`assert_terminates_on_inconsistent_order`], but also requires `sorter` never to panic,
+/// which rules out indexing out of bounds on the strength of an inconsistent comparison.
+///
+/// # Panics
+///
+/// Panics if the sorter panics, does not terminate in time or does not keep a permutation.
+pub fn assert_never_panics_on_inconsistent_order<S>(sorter: &S, config: &Config)
+where
+    S: for<'a> Sorter<Chaos<'a>> + Clone,
+{
+    for_each_inconsistent_input(config, |input, inconsistency, seed| {
+        if let Some(message) = sort_inconsistently(sorter, input, inconsistency, seed) {
+            panic!("{inconsistency:?} order on {input:?}: sorter panicked: {message}");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::StandardSorter;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn std_sort_terminates() {
+        assert_terminates_on_inconsistent_order(&StandardSorter, &Config::default());
+    }
+
+    #[test]
+    fn cyclic_order_is_not_transitive() {
+        let oracle = Oracle::new(Inconsistency::Cyclic, 0, usize::MAX);
+        let (a, b, c) = (
+            Chaos::new(0, &oracle),
+            Chaos::new(1, &oracle),
+            Chaos::new(2, &oracle),
+        );
+        assert!(a < b && b < c && c < a);
+    }
+
+    /// Bubble sort passes until no swap happens, with no bound on the number of passes.
+    #[derive(Clone)]
+    struct Unbounded;
+
+    impl<T: Ord> Sorter<T> for Unbounded {
+        fn sort(self, slice: &mut [T]) {
+            let mut swapped = true;
+            while swapped {
+                swapped = false;
+                for i in 1..slice.len() {
+                    if slice[i - 1] > slice[i] {
+                        slice.swap(i - 1, i);
+                        swapped = true;
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no termination")]
+    fn detects_endless_loops() {
+        assert_terminates_on_inconsistent_order(&Unbounded, &Config::default());
+    }
+
+    /// Insertion sort trusting the order to stop at the first element instead of checking bounds.
+    #[derive(Clone)]
+    struct Unguarded;
+
+    impl<T: Ord> Sorter<T> for Unguarded {
+        fn sort(self, slice: &mut [T]) {
+            for i in 1..slice.len() {
+                if slice[i] < slice[0] {
+                    slice[..=i].rotate_right(1);
+                    continue;
+                }
+                let mut j = i;
+                while slice[j - 1] > slice[j] {
+                    slice.swap(j - 1, j);
+                    j -= 1;
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "sorter panicked")]
+    fn detects_out_of_bounds_panics() {
+        assert_never_panics_on_inconsistent_order(&Unguarded, &Config::default());
+    }
+}
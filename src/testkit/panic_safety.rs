@@ -0,0 +1,214 @@
+use std::{
+    cmp::Ordering,
+    panic::{self, AssertUnwindSafe},
+    sync::atomic::{AtomicUsize, Ordering as AtomicOrdering},
+};
+
+use crate::{
+    Sorter,
+    dataset::{Distribution, Rng},
+};
+
+use super::Config;
+
+/// Shared state of the [`Bomb`]s of one input: counts comparisons and drops, and decides at
+/// which comparison to panic.
+#[derive(Debug)]
+pub struct Detonator {
+    comparisons: AtomicUsize,
+    drops: AtomicUsize,
+    panic_at: usize,
+}
+
+impl Detonator {
+    /// Creates a detonator panicking on comparison number `panic_at` (counted from zero), or
+    /// never if `panic_at` is `usize::MAX`.
+    #[must_use]
+    pub fn new(panic_at: usize) -> Self {
+        Self {
+            comparisons: AtomicUsize::new(0),
+            drops: AtomicUsize::new(0),
+            panic_at,
+        }
+    }
+
+    /// Number of comparisons made so far.
+    pub fn comparisons(&self) -> usize {
+        self.comparisons.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Number of [`Bomb`]s dropped so far.
+    pub fn drops(&self) -> usize {
+        self.drops.load(AtomicOrdering::Relaxed)
+    }
+}
+
+/// Heap-allocated element whose [`Ord`] implementation panics on a chosen comparison, and which
+/// counts its drops, see [`Detonator`].
+#[derive(Debug)]
+pub struct Bomb<'a> {
+    value: Box<i64>,
+    detonator: &'a Detonator,
+}
+
+impl<'a> Bomb<'a> {
+    /// Creates an element holding `value` and reporting to `detonator`.
+    pub fn new(value: i64, detonator: &'a Detonator) -> Self {
+        Self {
+            value: Box::new(value),
+            detonator,
+        }
+    }
+
+    /// Returns the value held by the element.
+    #[must_use]
+    pub fn value(&self) -> i64 {
+        *self.value
+    }
+}
+
+impl Drop for Bomb<'_> {
+    fn drop(&mut self) {
+        self.detonator.drops.fetch_add(1, AtomicOrdering::Relaxed);
+    }
+}
+
+impl Ord for Bomb<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let comparison = self
+            .detonator
+            .comparisons
+            .fetch_add(1, AtomicOrdering::Relaxed);
+        assert!(
+            comparison != self.detonator.panic_at,
+            "comparison {comparison} detonated"
+        );
+        self.value.cmp(&other.value)
+    }
+}
+
+impl PartialOrd for Bomb<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Bomb<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Bomb<'_> {}
+
+/// Sorts `input` with a comparison panicking at `panic_at`, and checks that the slice is left a
+/// permutation of `input` and that every element is dropped exactly once. Returns the number of
+/// comparisons made.
+fn sort_with_panic_at<S>(sorter: &S, input: &[i64], panic_at: usize) -> usize
+where
+    S: for<'a> Sorter<Bomb<'a>> + Clone,
+{
+    let detonator = Detonator::new(panic_at);
+    let mut elements: Vec<Bomb<'_>> = input
+        .iter()
+        .map(|&value| Bomb::new(value, &detonator))
+        .collect();
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| sorter.clone().sort(&mut elements)));
+    assert_eq!(
+        detonator.drops(),
+        0,
+        "an element was dropped while sorting {input:?} with a panic at comparison {panic_at}"
+    );
+
+    let mut values: Vec<i64> = elements.iter().map(Bomb::value).collect();
+    if outcome.is_ok() {
+        assert!(values.is_sorted(), "{input:?} sorted as {values:?}");
+    }
+    values.sort_unstable();
+    let mut expected = input.to_vec();
+    expected.sort_unstable();
+    assert!(
+        values == expected,
+        "panic at comparison {panic_at} left {input:?} as a non-permutation {:?}",
+        elements.iter().map(Bomb::value).collect::<Vec<_>>()
+    );
+
+    drop(elements);
+    assert_eq!(
+        detonator.drops(),
+        input.len(),
+        "elements leaked or duplicated"
+    );
+    detonator.comparisons()
+}
+
+/// Checks that `sorter` is panic safe: whichever comparison panics, the slice is left a
+/// permutation of the original with no element dropped, duplicated or leaked.
+///
+/// Every [`Distribution`] of [`Config::panic_len`] elements is first sorted without panicking to
+/// count its comparisons, then sorted again once per comparison index with a panic at that index.
+///
+/// # Panics
+///
+/// Panics if an element is dropped during the sort, or if the slice is not a permutation of the
+/// input afterwards.
+pub fn assert_panic_safe<S>(sorter: &S, config: &Config)
+where
+    S: for<'a> Sorter<Bomb<'a>> + Clone,
+{
+    let mut rng = Rng::new(config.seed);
+    for distribution in Distribution::ALL {
+        let input = distribution.generate(config.panic_len, &mut rng);
+        let comparisons = sort_with_panic_at(sorter, &input, usize::MAX);
+        for panic_at in 0..comparisons {
+            sort_with_panic_at(sorter, &input, panic_at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "alloc")]
+    use crate::StandardSorter;
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn std_sort_is_panic_safe() {
+        assert_panic_safe(&StandardSorter, &Config::default());
+    }
+
+    #[test]
+    fn bomb_detonates() {
+        let detonator = Detonator::new(1);
+        let (a, b) = (Bomb::new(1, &detonator), Bomb::new(2, &detonator));
+        assert!(a < b);
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| a < b));
+        assert!(outcome.is_err());
+        assert_eq!(detonator.comparisons(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-permutation")]
+    fn detects_duplicated_elements() {
+        /// Overwrites the first element with a clone of the second before comparing, which
+        /// duplicates a value if the comparison panics.
+        #[derive(Clone)]
+        struct Duplicating;
+
+        impl<'a> Sorter<Bomb<'a>> for Duplicating {
+            fn sort(self, slice: &mut [Bomb<'a>]) {
+                if slice.len() >= 2 {
+                    let saved = *slice[0].value;
+                    *slice[0].value = *slice[1].value;
+                    let _ = slice[0] < slice[1];
+                    *slice[0].value = saved;
+                }
+                slice.sort();
+            }
+        }
+
+        assert_panic_safe(&Duplicating, &Config::default());
+    }
+}
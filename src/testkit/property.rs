@@ -0,0 +1,252 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::{
+    Sorter,
+    dataset::{Distribution, Rng},
+};
+
+use super::{Config, Tagged};
+
+/// Outcome of a property on one input: `Err` carries the reason of the failure.
+pub type PropertyResult = Result<(), String>;
+
+/// Generates a random input of at most [`Config::property_max_len`] elements, mixing value
+/// ranges that produce many duplicates with the shapes of [`Distribution`].
+fn generate(rng: &mut Rng, config: &Config) -> Vec<i64> {
+    let len = rng.below(config.property_max_len + 1);
+    let bound = match rng.below(4) {
+        0 => 4,
+        1 => len.max(1),
+        2 => usize::MAX,
+        _ => {
+            let distribution = Distribution::ALL[rng.below(Distribution::ALL.len())];
+            return distribution.generate(len, rng);
+        }
+    };
+    (0..len)
+        .map(|_| i64::try_from(rng.below(bound)).unwrap_or(i64::MAX))
+        .collect()
+}
+
+/// Values closer to zero than `value`, most aggressive first.
+fn shrink_value(value: i64) -> impl Iterator<Item = i64> {
+    [0, value / 2, value - value.signum()]
+        .into_iter()
+        .filter(move |&candidate| candidate != value)
+}
+
+/// Inputs strictly simpler than `input`: first with chunks of decreasing size removed, then with
+/// every occurrence of a value moved closer to zero (which keeps duplicates duplicated), then with
+/// a single value moved closer to zero.
+fn shrink_candidates(input: &[i64]) -> impl Iterator<Item = Vec<i64>> + '_ {
+    let len = input.len();
+    let removals = std::iter::successors((len > 0).then_some(len), |&size| {
+        (size > 1).then_some(size / 2)
+    })
+    .flat_map(move |size| {
+        (0..len).step_by(size).map(move |start| {
+            let mut smaller = input[..start].to_vec();
+            smaller.extend_from_slice(&input[(start + size).min(len)..]);
+            smaller
+        })
+    });
+    let shared_simplifications = (0..len).flat_map(move |index| {
+        let value = input[index];
+        shrink_value(value).map(move |smaller| {
+            input
+                .iter()
+                .map(|&x| if x == value { smaller } else { x })
+                .collect()
+        })
+    });
+    let simplifications = (0..len).flat_map(move |index| {
+        shrink_value(input[index]).map(move |value| {
+            let mut simpler = input.to_vec();
+            simpler[index] = value;
+            simpler
+        })
+    });
+    removals
+        .chain(shared_simplifications)
+        .chain(simplifications)
+}
+
+/// Runs `property`, turning a panic into a failure so that panicking inputs can be shrunk too.
+fn run(property: &impl Fn(&[i64]) -> PropertyResult, input: &[i64]) -> PropertyResult {
+    panic::catch_unwind(AssertUnwindSafe(|| property(input))).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(ToString::to_string)
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        Err(format!("panicked: {message}"))
+    })
+}
+
+/// Greedily shrinks a failing `input` until none of its simpler variants fails anymore, and
+/// returns the minimal counterexample with its failure. The variants tried are, in order, the
+/// input with chunks of halving sizes removed, with every occurrence of a value moved closer to
+/// zero, then with a single value moved closer to zero.
+pub fn shrink(
+    input: Vec<i64>,
+    reason: String,
+    property: impl Fn(&[i64]) -> PropertyResult,
+) -> (Vec<i64>, String) {
+    let mut minimal = (input, reason);
+    loop {
+        let simpler = shrink_candidates(&minimal.0).find_map(|candidate| {
+            run(&property, &candidate)
+                .err()
+                .map(|reason| (candidate, reason))
+        });
+        match simpler {
+            Some(simpler) => minimal = simpler,
+            None => return minimal,
+        }
+    }
+}
+
+/// Checks `property` on [`Config::property_cases`] random inputs generated from [`Config::seed`].
+///
+/// # Panics
+///
+/// Panics if the property fails, reporting the original input and the minimal counterexample it
+/// was [shrunk](shrink) to.
+pub fn check(config: &Config, property: impl Fn(&[i64]) -> PropertyResult) {
+    let mut rng = Rng::new(config.seed);
+    for case in 0..config.property_cases {
+        let input = generate(&mut rng, config);
+        if let Err(reason) = run(&property, &input) {
+            let (minimal, minimal_reason) = shrink(input.clone(), reason, &property);
+            panic!(
+                "property failed on case {case} (seed {seed:#x})\n  original: {input:?}\n  \
+                 minimal:  {minimal:?}\n  reason:   {minimal_reason}",
+                seed = config.seed
+            );
+        }
+    }
+}
+
+/// Property: sorting `input` with `sorter` yields a sorted permutation of `input`.
+///
+/// # Errors
+///
+/// Describes the output if it is unsorted or not a permutation of `input`.
+pub fn sorted_permutation<S: Sorter<i64> + Clone>(sorter: &S, input: &[i64]) -> PropertyResult {
+    let mut output = input.to_vec();
+    sorter.clone().sort(&mut output);
+    if !output.is_sorted() {
+        return Err(format!("output is not sorted: {output:?}"));
+    }
+    let mut expected = input.to_vec();
+    expected.sort_unstable();
+    if output != expected {
+        return Err(format!(
+            "output is not a permutation of the input: {output:?}"
+        ));
+    }
+    Ok(())
+}
+
+/// Property: sorting `input` keys tagged with their position keeps equal keys in tag order.
+///
+/// # Errors
+///
+/// Describes the first pair of equal keys found out of order.
+pub fn stable_tag_order<S: Sorter<Tagged> + Clone>(sorter: &S, input: &[i64]) -> PropertyResult {
+    let mut output: Vec<Tagged> = input
+        .iter()
+        .enumerate()
+        .map(|(tag, &key)| Tagged { key, tag })
+        .collect();
+    sorter.clone().sort(&mut output);
+    match output
+        .windows(2)
+        .find(|pair| pair[0].key == pair[1].key && pair[0].tag > pair[1].tag)
+    {
+        Some(pair) => Err(format!(
+            "equal keys out of order: {:?} before {:?}",
+            pair[0], pair[1]
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Checks the [`sorted_permutation`] property of `sorter`.
+///
+/// # Panics
+///
+/// Panics with a minimal counterexample if the property fails.
+pub fn assert_sorted_permutation_property<S: Sorter<i64> + Clone>(sorter: &S, config: &Config) {
+    check(config, |input| sorted_permutation(sorter, input));
+}
+
+/// Checks the [`stable_tag_order`] property of `sorter`.
+///
+/// # Panics
+///
+/// Panics with a minimal counterexample if the property fails.
+pub fn assert_stable_property<S: Sorter<Tagged> + Clone>(sorter: &S, config: &Config) {
+    check(config, |input| stable_tag_order(sorter, input));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrinks_to_minimal_counterexample() {
+        // Fails as soon as the input holds a value greater than 10.
+        let property = |input: &[i64]| match input.iter().find(|&&x| x > 10) {
+            Some(x) => Err(format!("{x} is too big")),
+            None => Ok(()),
+        };
+        let input = vec![3, -7, 250, 8, 1000, 0];
+        let (minimal, reason) = shrink(input, String::new(), property);
+        assert_eq!(minimal, vec![11]);
+        assert_eq!(reason, "11 is too big");
+    }
+
+    #[test]
+    fn shrinks_panics() {
+        let property = |input: &[i64]| {
+            assert!(input.len() < 3, "too long");
+            Ok(())
+        };
+        let (minimal, reason) = shrink(vec![5, 6, 7, 8, 9], String::new(), property);
+        assert_eq!(minimal, vec![0, 0, 0]);
+        assert!(reason.contains("too long"), "{reason}");
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal:  [1, 0]")]
+    fn reports_minimal_unsorted_input() {
+        #[derive(Clone)]
+        struct SkipsLastPair;
+
+        impl<T: Ord> Sorter<T> for SkipsLastPair {
+            fn sort(self, slice: &mut [T]) {
+                let len = slice.len();
+                slice[..len.saturating_sub(1)].sort();
+            }
+        }
+
+        assert_sorted_permutation_property(&SkipsLastPair, &Config::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal:  [0, 0]")]
+    fn reports_minimal_unstable_input() {
+        #[derive(Clone)]
+        struct ReversesInput;
+
+        impl<T: Ord> Sorter<T> for ReversesInput {
+            fn sort(self, slice: &mut [T]) {
+                slice.reverse();
+                slice.sort();
+            }
+        }
+
+        assert_stable_property(&ReversesInput, &Config::default());
+    }
+}
@@ -0,0 +1,74 @@
+//! Checks the heap usage of the sorters with the counting allocator installed globally, which
+//! needs a binary of its own.
+
+use std::sync::Mutex;
+
+use maniac::{
+    bench::{Benchmarker, CountingAllocator},
+    dataset::Distribution,
+    registry,
+};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+
+/// The counters are global, so the tests must not allocate concurrently.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+#[test]
+fn in_place_sorters_do_not_allocate() {
+    let _guard = SERIAL.lock().unwrap();
+    let benchmarker = Benchmarker {
+        sizes: vec![0, 10, 500],
+        samples: 1,
+        ..Benchmarker::default()
+    }
+    .with_allocator(&ALLOCATOR);
+    let sorters: Vec<_> = registry::all()
+        .into_iter()
+        .filter(|entry| entry.metadata.in_place && !entry.metadata.parallel)
+        .collect();
+    assert!(sorters.iter().any(|entry| entry.name == "comb"));
+
+    for measurement in benchmarker.run(&sorters) {
+        let stats = measurement.allocations.expect("the allocator is set");
+        assert_eq!(
+            stats.allocations, 0,
+            "{}/{} allocated on {:?} of length {}: {stats}",
+            measurement.name, measurement.variant, measurement.distribution, measurement.len
+        );
+    }
+}
+
+#[test]
+fn standard_sort_uses_a_buffer() {
+    let _guard = SERIAL.lock().unwrap();
+    let benchmarker = Benchmarker {
+        sizes: vec![10_000],
+        distributions: vec![Distribution::Random],
+        samples: 1,
+        ..Benchmarker::default()
+    }
+    .with_allocator(&ALLOCATOR);
+    let standard = registry::find::<i64>("standard", "default").unwrap();
+
+    let measurements = benchmarker.measure(standard.name, standard.variant, &*standard.sorter);
+    let stats = measurements[0].allocations.unwrap();
+    assert!(stats.allocations >= 1, "{stats}");
+    assert!(stats.peak_bytes >= stats.allocated_bytes / 2, "{stats}");
+    assert!(stats.peak_bytes > 0, "{stats}");
+}
+
+#[test]
+fn measure_reports_the_peak() {
+    let _guard = SERIAL.lock().unwrap();
+    let ((), stats) = ALLOCATOR.measure(|| {
+        let first = vec![0_u8; 1_000];
+        drop(first);
+        let second = vec![0_u8; 400];
+        drop(second);
+    });
+    assert_eq!(stats.allocations, 2);
+    assert_eq!(stats.allocated_bytes, 1_400);
+    assert_eq!(stats.peak_bytes, 1_000);
+}